@@ -0,0 +1,127 @@
+//! Generate `assets.rs`, a constant with the served URL of every file under `static/`,
+//! and `asset-manifest.json` next to it mapping each file to the same URL. Both are only written under `OUT_DIR`,
+//! a tool needing the URLs computes them with `portfolio_assets::bundled_name` as `site_builder` does.
+//!
+//! Parcel is run with `--no-content-hash`, which names a file `<stem>.<hash>.<ext>`
+//! with the last 8 hex digits of the MD5 of its path relative to `static/`. The same name is computed here,
+//! so a URL in Rust is always the one parcel writes, and a file that does not exist has no constant.
+//! The files made by another step, listed in `GENERATED`, are the exception: they are not committed,
+//! so their constants always exist and a missing one only gets a warning naming the step that makes it.
+//! Each of them that exists is told to the code as `cfg(generated = "<file>")`.
+//!
+//! With the `embedded-data` feature the data files are compiled in, and each of them is parsed with its model type here first.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use portfolio_assets::bundled_name;
+use portfolio_model::license::LicenseDocument;
+use portfolio_model::link::LinkDocument;
+use portfolio_model::profile::ProfileDocument;
+use portfolio_model::qualification::QualificationDocument;
+use portfolio_model::works::WorksData;
+use serde::de::DeserializeOwned;
+
+const STATIC_DIR: &str = "static";
+/// Compiled by parcel into bundles of its own instead of being copied
+const SOURCES: [&str; 5] = ["index.html", "index.paths.html", "index.paths.ts", "index.ts", "styles.scss"];
+/// Files under `static/` that are not committed, with the step making each of them
+const GENERATED: [(&str, &str); 7] = [
+    ("works.data.json", "./generate_works.ps1"),
+    ("works.graph.svg", "./generate_works.ps1"),
+    ("license.data.json.sig", SIGN),
+    ("link.data.json.sig", SIGN),
+    ("profile.data.json.sig", SIGN),
+    ("qualification.data.json.sig", SIGN),
+    ("works.data.json.sig", SIGN),
+];
+const SIGN: &str = "cargo run -p encrypter -- sign <secret key file> static/*.data.json";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", STATIC_DIR);
+    println!("cargo:rerun-if-env-changed=PORTFOLIO_SIGNING_KEY");
+    let mut files = Vec::new();
+    collect_files(Path::new(STATIC_DIR), &mut files);
+    let mut assets: Vec<String> = files.iter()
+        .map(|path| {
+            path.strip_prefix(STATIC_DIR).unwrap().components()
+                .map(|c| c.as_os_str().to_str().expect("asset path is not UTF-8"))
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .filter(|relative| !SOURCES.contains(&relative.as_str()))
+        .collect();
+    let signed = std::env::var_os("PORTFOLIO_SIGNING_KEY").is_some();
+    let generated_values = GENERATED.iter().map(|(relative, _)| format!("{:?}", relative)).collect::<Vec<_>>().join(", ");
+    println!("cargo:rustc-check-cfg=cfg(generated, values({}))", generated_values);
+    for (relative, step) in GENERATED.iter() {
+        if assets.iter().any(|asset| asset == relative) {
+            println!("cargo:rustc-cfg=generated={:?}", relative);
+            continue;
+        }
+        // signatures are only fetched by a build that verifies them
+        if step != &SIGN || signed {
+            println!("cargo:warning=static/{} does not exist, run `{}` to make it", relative, step);
+        }
+        assets.push(relative.to_string());
+    }
+    let mut assets: Vec<(String, String)> = assets.into_iter()
+        .map(|relative| {
+            let url = format!("/{}", bundled_name(&relative));
+            (relative, url)
+        })
+        .collect();
+    assets.sort();
+
+    let mut constants = String::from("// generated by build.rs from static/, do not edit\n");
+    let mut manifest = String::from("{\n");
+    for (i, (relative, url)) in assets.iter().enumerate() {
+        writeln!(constants, "/// `static/{}`", relative).unwrap();
+        writeln!(constants, "pub const {}: &str = {:?};", constant_name(relative), url).unwrap();
+        let separator = if i + 1 == assets.len() { "" } else { "," };
+        writeln!(manifest, "  {:?}: {:?}{}", relative, url, separator).unwrap();
+    }
+    manifest.push_str("}\n");
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    std::fs::write(out_dir.join("assets.rs"), constants).expect("failed to write assets.rs");
+    std::fs::write(out_dir.join("asset-manifest.json"), manifest).expect("failed to write asset-manifest.json");
+
+    if std::env::var_os("CARGO_FEATURE_EMBEDDED_DATA").is_some() {
+        check_data::<LinkDocument>("link.data.json");
+        check_data::<QualificationDocument>("qualification.data.json");
+        check_data::<ProfileDocument>("profile.data.json");
+        check_data::<LicenseDocument>("license.data.json");
+        // embedded only once generated, fetched until then
+        if Path::new(STATIC_DIR).join("works.data.json").is_file() {
+            check_data::<WorksData>("works.data.json");
+        }
+    }
+}
+
+/// Fail the build if `static/<file>` does not parse as `T`
+fn check_data<T: DeserializeOwned>(file: &str) {
+    let path = Path::new(STATIC_DIR).join(file);
+    let content = std::fs::read(&path).unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+    if let Err(e) = serde_json::from_slice::<T>(&content) {
+        panic!("{} is malformed: {}", path.display(), e);
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).expect("failed to read static/") {
+        let path = entry.expect("failed to read static/").path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if !path.file_name().unwrap().to_string_lossy().starts_with('.') {
+            files.push(path);
+        }
+    }
+}
+
+/// `GitHub-Mark-120px-plus.png` -> `GITHUB_MARK_120PX_PLUS_PNG`
+fn constant_name(relative: &str) -> String {
+    relative.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
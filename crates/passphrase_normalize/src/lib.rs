@@ -0,0 +1,21 @@
+use unicode_normalization::UnicodeNormalization;
+
+/// Offset between a katakana and the hiragana with the same reading (ア U+30A2 -> あ U+3042)
+const KATAKANA_HIRAGANA_OFFSET: u32 = 0x60;
+
+/// Normalize a passphrase (the owner's name in kana) so that every way of typing it yields the same key.
+///
+/// - NFKC, so half-width katakana and decomposed dakuten are unified
+/// - katakana are folded into hiragana
+/// - dash-like characters are folded into the long vowel mark `ー`
+/// - everything else, including half-width and full-width spaces, is dropped
+pub fn normalize(input: &str) -> String {
+    input.nfkc()
+        .filter_map(|c| match c {
+            'ぁ'..='ゖ' => Some(c),
+            'ァ'..='ヶ' => std::char::from_u32(c as u32 - KATAKANA_HIRAGANA_OFFSET),
+            'ー' | '-' | '‐' | '−' | '―' | '〜' | '~' => Some('ー'),
+            _ => None,
+        })
+        .collect()
+}
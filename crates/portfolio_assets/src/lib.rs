@@ -0,0 +1,13 @@
+/// Name parcel gives `static/<relative>` when run with `--no-content-hash`: `works.data.json` -> `works.data.9a90fd0b.json`.
+///
+/// The hash is the last 8 hex digits of the MD5 of the path relative to `static/`, with `/` as the separator,
+/// so the site, its build script and the tools all compute the URL parcel writes without running it.
+pub fn bundled_name(relative: &str) -> String {
+    let hash = format!("{:x}", md5::compute(relative));
+    let hash = &hash[hash.len() - 8..];
+    let file_name = relative.rsplit('/').next().unwrap();
+    match file_name.rfind('.') {
+        Some(dot) => format!("{}.{}{}", &file_name[..dot], hash, &file_name[dot..]),
+        None => format!("{}.{}", file_name, hash),
+    }
+}
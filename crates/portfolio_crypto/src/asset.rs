@@ -0,0 +1,88 @@
+//! Sealed binary asset layout (all integers little endian):
+//!
+//! | field          | size |
+//! |----------------|------|
+//! | magic          | 4    |
+//! | version        | 1    |
+//! | mime length    | 1    |
+//! | mime type      | n    |
+//! | keyring length | 4    |
+//! | keyring        | n    |
+//! | sealed box     | rest |
+//!
+//! The box is sealed with the data key of id 0 in the asset's own [`Keyring`],
+//! so an asset can be given to a different set of recipients than the data files.
+
+use std::convert::TryInto;
+
+use crate::{Key, KeySource, Keyring, NONCE_SIZE, OpenError, RecipientKeys};
+
+pub const ASSET_MAGIC: &[u8; 4] = b"WGPA";
+pub const ASSET_VERSION: u8 = 1;
+
+/// Id of the data key the content is sealed with
+pub const ASSET_KEY_ID: usize = 0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SealedAsset {
+    pub mime: String,
+    pub keyring: Keyring,
+    sealed: Vec<u8>,
+}
+
+impl SealedAsset {
+    /// Seal `content` with `key`, which has to be wrapped as [`ASSET_KEY_ID`] in `keyring`.
+    pub fn seal(mime: &str, content: &[u8], key: &Key, keyring: Keyring, nonce: [u8; NONCE_SIZE]) -> Self {
+        assert!(mime.len() <= u8::MAX as usize, "mime type is too long");
+        SealedAsset { mime: mime.to_string(), keyring, sealed: crate::seal(content, b"", key, nonce) }
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, OpenError> {
+        if data.len() < ASSET_MAGIC.len() + 1 || &data[..ASSET_MAGIC.len()] != ASSET_MAGIC {
+            return Err(OpenError::NotSealed);
+        }
+        let version = data[ASSET_MAGIC.len()];
+        if version != ASSET_VERSION {
+            return Err(OpenError::UnsupportedVersion(version));
+        }
+        let rest = &data[ASSET_MAGIC.len() + 1..];
+        let (&mime_len, rest) = rest.split_first().ok_or(OpenError::Corrupted)?;
+        if rest.len() < mime_len as usize + 4 {
+            return Err(OpenError::Corrupted);
+        }
+        let (mime, rest) = rest.split_at(mime_len as usize);
+        let mime = String::from_utf8(mime.to_vec()).map_err(|_| OpenError::Corrupted)?;
+        let (keyring_len, rest) = rest.split_at(4);
+        let keyring_len = u32::from_le_bytes(keyring_len.try_into().unwrap()) as usize;
+        if rest.len() < keyring_len {
+            return Err(OpenError::Corrupted);
+        }
+        let (keyring, sealed) = rest.split_at(keyring_len);
+        Ok(SealedAsset { mime, keyring: Keyring::parse(keyring)?, sealed: sealed.to_vec() })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let keyring = self.keyring.to_bytes();
+        let mut result = Vec::with_capacity(ASSET_MAGIC.len() + 2 + self.mime.len() + 4 + keyring.len() + self.sealed.len());
+        result.extend_from_slice(ASSET_MAGIC);
+        result.push(ASSET_VERSION);
+        result.push(self.mime.len() as u8);
+        result.extend_from_slice(self.mime.as_bytes());
+        result.extend_from_slice(&(keyring.len() as u32).to_le_bytes());
+        result.extend_from_slice(&keyring);
+        result.extend_from_slice(&self.sealed);
+        result
+    }
+
+    /// Decrypt the content with the data key `key`, such as a passphrase, unwraps from the keyring.
+    pub fn open<K: KeySource + ?Sized>(&self, key: &K) -> Result<Vec<u8>, OpenError> {
+        self.open_with(&self.keyring.recipient_keys(key)?)
+    }
+
+    /// Decrypt the content with keys already unwrapped from the keyring.
+    /// A recipient that was not given the asset's data key gets [`OpenError::WrongKey`].
+    pub fn open_with(&self, keys: &RecipientKeys) -> Result<Vec<u8>, OpenError> {
+        let key = keys.get(ASSET_KEY_ID).ok_or(OpenError::WrongKey)?;
+        crate::open(&self.sealed, b"", key)
+    }
+}
@@ -0,0 +1,119 @@
+//! Sealed box layout:
+//!
+//! | field         | size |
+//! |---------------|------|
+//! | nonce         | 16   |
+//! | key check     | 8    |
+//! | plaintext len | 8    |
+//! | ciphertext    | n    |
+//! | tag           | 32   |
+//!
+//! `tag` is HMAC-SHA3-256 over everything before it (encrypt-then-MAC), followed by the associated data
+//! and its length as 8 bytes little endian when there is any. The associated data is not stored in the box,
+//! the reader gives the same again, so a box only opens in the place it was sealed for.
+//! `key check` lets a wrong key be told apart from a tampered box.
+
+use std::convert::TryInto;
+use std::fmt::{Display, Formatter};
+
+use hmac::{Hmac, Mac, NewMac};
+use sha3::{Digest, Sha3_256};
+
+use crate::Key;
+
+pub const NONCE_SIZE: usize = 16;
+
+const KEY_CHECK_SIZE: usize = 8;
+const LENGTH_SIZE: usize = 8;
+const TAG_SIZE: usize = 32;
+const HEADER_SIZE: usize = NONCE_SIZE + KEY_CHECK_SIZE + LENGTH_SIZE;
+
+/// Failure to open a sealed box, a [`Keyring`](crate::Keyring) or a [`SealedAsset`](crate::SealedAsset).
+/// [`open`] only returns `Corrupted` and `WrongKey`, as the magic bytes and the version are in the keyring and the asset header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenError {
+    /// magic bytes of a keyring or an asset do not match, the data is not sealed
+    NotSealed,
+    /// keyring or asset written with a format version this build does not read
+    UnsupportedVersion(u8),
+    /// data was changed or cut short
+    Corrupted,
+    /// sealed with another key
+    WrongKey,
+}
+
+impl Display for OpenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpenError::NotSealed => write!(f, "not an encrypted portfolio file"),
+            OpenError::UnsupportedVersion(v) => write!(f, "unsupported format version {} (expected {})", v, crate::FORMAT_VERSION),
+            OpenError::Corrupted => write!(f, "file is corrupted"),
+            OpenError::WrongKey => write!(f, "wrong key"),
+        }
+    }
+}
+
+impl std::error::Error for OpenError {}
+
+type HmacSha3_256 = Hmac<Sha3_256>;
+
+fn sub_key(label: &[u8], key: &Key, nonce: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(label);
+    hasher.update(key.as_bytes());
+    hasher.update(nonce);
+    hasher.finalize().into()
+}
+
+fn key_check(key: &Key, nonce: &[u8]) -> [u8; KEY_CHECK_SIZE] {
+    sub_key(b"portfolio-check", key, nonce)[..KEY_CHECK_SIZE].try_into().unwrap()
+}
+
+fn mac(key: &Key, nonce: &[u8], signed: &[u8], associated_data: &[u8]) -> HmacSha3_256 {
+    let mut mac = HmacSha3_256::new_varkey(&sub_key(b"portfolio-mac", key, nonce)).expect("HMAC accepts any key length");
+    mac.update(signed);
+    if !associated_data.is_empty() {
+        mac.update(associated_data);
+        mac.update(&(associated_data.len() as u64).to_le_bytes());
+    }
+    mac
+}
+
+/// Encrypt `plaintext` into a sealed box bound to `associated_data`. `nonce` must be freshly random for every call.
+pub fn seal(plaintext: &[u8], associated_data: &[u8], key: &Key, nonce: [u8; NONCE_SIZE]) -> Vec<u8> {
+    let enc_key = aes::Key::AES256(sub_key(b"portfolio-enc", key, &nonce));
+    let ciphertext = aes::encrypt(plaintext, &enc_key);
+
+    let mut result = Vec::with_capacity(HEADER_SIZE + ciphertext.len() + TAG_SIZE);
+    result.extend_from_slice(&nonce);
+    result.extend_from_slice(&key_check(key, &nonce));
+    result.extend_from_slice(&(plaintext.len() as u64).to_le_bytes());
+    result.extend_from_slice(&ciphertext);
+
+    let tag = mac(key, &nonce, &result, associated_data).finalize().into_bytes();
+    result.extend_from_slice(&tag);
+    result
+}
+
+/// Verify and decrypt a box created by [`seal`] with the same `associated_data`.
+pub fn open(data: &[u8], associated_data: &[u8], key: &Key) -> Result<Vec<u8>, OpenError> {
+    if data.len() < HEADER_SIZE + TAG_SIZE {
+        return Err(OpenError::Corrupted);
+    }
+    let (signed, tag) = data.split_at(data.len() - TAG_SIZE);
+    let nonce = &signed[..NONCE_SIZE];
+    if signed[NONCE_SIZE..NONCE_SIZE + KEY_CHECK_SIZE] != key_check(key, nonce) {
+        return Err(OpenError::WrongKey);
+    }
+    let length = u64::from_le_bytes(signed[NONCE_SIZE + KEY_CHECK_SIZE..HEADER_SIZE].try_into().unwrap()) as usize;
+
+    mac(key, nonce, signed, associated_data).verify(tag).map_err(|_| OpenError::Corrupted)?;
+
+    let enc_key = aes::Key::AES256(sub_key(b"portfolio-enc", key, nonce));
+    let mut plaintext = aes::decrypt(&signed[HEADER_SIZE..], &enc_key);
+    if plaintext.len() < length {
+        return Err(OpenError::Corrupted);
+    }
+    plaintext.truncate(length);
+    Ok(plaintext)
+}
@@ -0,0 +1,26 @@
+use hmac::Hmac;
+use sha3::Sha3_256;
+
+use crate::Key;
+
+pub const SALT_SIZE: usize = 16;
+pub const DEFAULT_ITERATIONS: u32 = 200_000;
+
+/// PBKDF2-HMAC-SHA3-256 parameters, stored in the header of every sealed file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KdfParams {
+    pub salt: [u8; SALT_SIZE],
+    pub iterations: u32,
+}
+
+impl KdfParams {
+    pub fn new(salt: [u8; SALT_SIZE]) -> Self {
+        KdfParams { salt, iterations: DEFAULT_ITERATIONS }
+    }
+}
+
+pub fn derive_key(passphrase: &str, params: &KdfParams) -> Key {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha3_256>>(passphrase.as_bytes(), &params.salt, params.iterations, &mut key);
+    Key::from(key)
+}
@@ -0,0 +1,263 @@
+//! Keyring layout (all integers little endian), embedded base64 encoded in the public data:
+//!
+//! | field           | size                |
+//! |-----------------|---------------------|
+//! | magic           | 4                   |
+//! | version         | 1                   |
+//! | recipient count | 1                   |
+//! | recipients      | see below, repeated |
+//! | checksum        | 8                   |
+//!
+//! Values are sealed with random data keys, identified by a small integer id.
+//! A recipient is a label, the [`KdfParams`] of its passphrase and a sealed box holding
+//! the data keys it may use, so each passphrase unlocks its own subset of the values.
+//!
+//! | recipient field | size |
+//! |-----------------|------|
+//! | label length    | 1    |
+//! | label           | n    |
+//! | iterations      | 4    |
+//! | salt            | 16   |
+//! | wrapped length  | 4    |
+//! | wrapped keys    | n    |
+//!
+//! The wrapped keys are a sealed box of the recipient's [`Validity`] followed by its data keys,
+//! so the window cannot be changed without the passphrase.
+//!
+//! | wrapped field | size             |
+//! |---------------|------------------|
+//! | not before    | 8                |
+//! | not after     | 8                |
+//! | key count     | 2                |
+//! | keys          | (2 + 32) * count |
+//!
+//! Times are unix seconds, `0` and `u64::MAX` standing for no limit.
+//!
+//! `checksum` is an unkeyed SHA3-256 prefix used to tell a broken keyring from a wrong key.
+//!
+//! Each value is sealed with the JSON pointer of its place in the data as associated data,
+//! such as `/1/values/0`, so values cannot be swapped or copied within the public JSON.
+
+use std::convert::TryInto;
+
+use sha3::{Digest, Sha3_256};
+
+use crate::{KdfParams, Key, KeySource, NONCE_SIZE, OpenError, SALT_SIZE};
+
+pub const MAGIC: &[u8; 4] = b"WGPF";
+pub const FORMAT_VERSION: u8 = 6;
+
+const CHECKSUM_SIZE: usize = 8;
+const WRAPPED_KEY_SIZE: usize = 2 + 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipient {
+    pub label: String,
+    pub params: KdfParams,
+    wrapped: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Keyring {
+    recipients: Vec<Recipient>,
+}
+
+/// Period in unix seconds a recipient's passphrase is meant to be used in.
+/// The keyring only carries it, the reader has the clock and enforces it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Validity {
+    pub not_before: Option<u64>,
+    pub not_after: Option<u64>,
+}
+
+impl Validity {
+    pub fn is_unlimited(&self) -> bool {
+        self.not_before.is_none() && self.not_after.is_none()
+    }
+
+    pub fn contains(&self, now: u64) -> bool {
+        self.not_before.iter().all(|&t| t <= now) && self.not_after.iter().all(|&t| now <= t)
+    }
+}
+
+/// Data keys a recipient's passphrase gives access to, by key id
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipientKeys {
+    pub label: String,
+    pub validity: Validity,
+    pub keys: Vec<(usize, Key)>,
+}
+
+impl RecipientKeys {
+    pub fn get(&self, id: usize) -> Option<&Key> {
+        self.keys.iter().find(|(i, _)| *i == id).map(|(_, key)| key)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], OpenError> {
+        if self.0.len() < len {
+            return Err(OpenError::Corrupted);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, OpenError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, OpenError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, OpenError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, OpenError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    Sha3_256::digest(data)[..CHECKSUM_SIZE].try_into().unwrap()
+}
+
+impl Keyring {
+    pub fn parse(data: &[u8]) -> Result<Self, OpenError> {
+        if data.len() < MAGIC.len() + 1 || &data[..MAGIC.len()] != MAGIC {
+            return Err(OpenError::NotSealed);
+        }
+        let version = data[MAGIC.len()];
+        if version != FORMAT_VERSION {
+            return Err(OpenError::UnsupportedVersion(version));
+        }
+        if data.len() < MAGIC.len() + 1 + CHECKSUM_SIZE {
+            return Err(OpenError::Corrupted);
+        }
+        let (body, stored_checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+        if checksum(body) != stored_checksum {
+            return Err(OpenError::Corrupted);
+        }
+
+        let mut reader = Reader(&body[MAGIC.len() + 1..]);
+        let mut recipients = Vec::new();
+        for _ in 0..reader.u8()? {
+            let label_len = reader.u8()? as usize;
+            let label = String::from_utf8(reader.bytes(label_len)?.to_vec()).map_err(|_| OpenError::Corrupted)?;
+            let iterations = reader.u32()?;
+            if iterations == 0 {
+                return Err(OpenError::Corrupted);
+            }
+            let salt = reader.bytes(SALT_SIZE)?.try_into().unwrap();
+            let wrapped_len = reader.u32()? as usize;
+            let wrapped = reader.bytes(wrapped_len)?.to_vec();
+            recipients.push(Recipient { label, params: KdfParams { salt, iterations }, wrapped });
+        }
+        if !reader.0.is_empty() {
+            return Err(OpenError::Corrupted);
+        }
+        Ok(Keyring { recipients })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        result.extend_from_slice(MAGIC);
+        result.push(FORMAT_VERSION);
+        result.push(self.recipients.len() as u8);
+        for Recipient { label, params, wrapped } in &self.recipients {
+            result.push(label.len() as u8);
+            result.extend_from_slice(label.as_bytes());
+            result.extend_from_slice(&params.iterations.to_le_bytes());
+            result.extend_from_slice(&params.salt);
+            result.extend_from_slice(&(wrapped.len() as u32).to_le_bytes());
+            result.extend_from_slice(wrapped);
+        }
+        let checksum = checksum(&result);
+        result.extend_from_slice(&checksum);
+        result
+    }
+
+    /// Parse the base64 form stored in the public JSON.
+    pub fn decode(text: &str) -> Result<Self, OpenError> {
+        let data = base64::decode(text).map_err(|_| OpenError::Corrupted)?;
+        Keyring::parse(&data)
+    }
+
+    pub fn encode(&self) -> String {
+        base64::encode(self.to_bytes())
+    }
+
+    pub fn recipients(&self) -> &[Recipient] {
+        &self.recipients
+    }
+
+    /// Wrap `keys` for a recipient whose key is derived with `params`, to be used within `validity`.
+    /// A recipient with the same label is replaced.
+    pub fn add_recipient(&mut self, label: &str, key: &Key, params: KdfParams, validity: Validity, keys: &[(usize, Key)], nonce: [u8; NONCE_SIZE]) {
+        assert!(label.len() <= u8::MAX as usize, "recipient label is too long");
+        let mut wrapped = Vec::with_capacity(8 + 8 + 2 + keys.len() * WRAPPED_KEY_SIZE);
+        wrapped.extend_from_slice(&validity.not_before.unwrap_or(0).to_le_bytes());
+        wrapped.extend_from_slice(&validity.not_after.unwrap_or(u64::MAX).to_le_bytes());
+        wrapped.extend_from_slice(&(keys.len() as u16).to_le_bytes());
+        for (id, data_key) in keys {
+            assert!(*id <= u16::MAX as usize, "key id {} is out of range", id);
+            wrapped.extend_from_slice(&(*id as u16).to_le_bytes());
+            wrapped.extend_from_slice(data_key.as_bytes());
+        }
+        self.remove_recipient(label);
+        assert!(self.recipients.len() < u8::MAX as usize, "too many recipients");
+        self.recipients.push(Recipient { label: label.to_string(), params, wrapped: crate::seal(&wrapped, b"", key, nonce) });
+    }
+
+    pub fn remove_recipient(&mut self, label: &str) -> bool {
+        let len = self.recipients.len();
+        self.recipients.retain(|r| r.label != label);
+        self.recipients.len() != len
+    }
+
+    /// Find the recipient `key` belongs to and unwrap its data keys.
+    pub fn recipient_keys<K: KeySource + ?Sized>(&self, key: &K) -> Result<RecipientKeys, OpenError> {
+        for recipient in &self.recipients {
+            let recipient_key = match key.key_for(&recipient.params) {
+                Some(recipient_key) => recipient_key,
+                None => continue,
+            };
+            let keys = match crate::open(&recipient.wrapped, b"", &recipient_key) {
+                Ok(keys) => keys,
+                Err(OpenError::WrongKey) => continue,
+                Err(e) => return Err(e),
+            };
+            let mut reader = Reader(&keys);
+            let validity = Validity {
+                not_before: Some(reader.u64()?).filter(|&t| t != 0),
+                not_after: Some(reader.u64()?).filter(|&t| t != u64::MAX),
+            };
+            let count = reader.u16()?;
+            let keys = (0..count)
+                .map(|_| {
+                    let id = reader.u16()? as usize;
+                    let key: [u8; 32] = reader.bytes(32)?.try_into().unwrap();
+                    Ok((id, Key::from(key)))
+                })
+                .collect::<Result<_, _>>()?;
+            return Ok(RecipientKeys { label: recipient.label.clone(), validity, keys });
+        }
+        Err(OpenError::WrongKey)
+    }
+}
+
+/// Seal a single value for its place `path` in the data and encode it as base64 for the public JSON.
+pub fn seal_field(plaintext: &[u8], path: &str, key: &Key, nonce: [u8; NONCE_SIZE]) -> String {
+    base64::encode(crate::seal(plaintext, path.as_bytes(), key, nonce))
+}
+
+/// Decode and open a value sealed by [`seal_field`] for the same `path`.
+pub fn open_field(text: &str, path: &str, key: &Key) -> Result<Vec<u8>, OpenError> {
+    let data = base64::decode(text).map_err(|_| OpenError::Corrupted)?;
+    crate::open(&data, path.as_bytes(), key)
+}
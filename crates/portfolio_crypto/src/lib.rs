@@ -0,0 +1,69 @@
+pub use asset::{ASSET_KEY_ID, ASSET_MAGIC, ASSET_VERSION, SealedAsset};
+pub use container::{NONCE_SIZE, OpenError, open, seal};
+pub use keyring::{FORMAT_VERSION, Keyring, MAGIC, Recipient, RecipientKeys, Validity, open_field, seal_field};
+pub use kdf::{DEFAULT_ITERATIONS, KdfParams, SALT_SIZE, derive_key};
+pub use signature::{SignatureError, SigningKey, VerifyingKey};
+
+mod asset;
+mod container;
+mod kdf;
+mod keyring;
+mod signature;
+
+/// 256bit key, either a random data key or derived from a passphrase and [`KdfParams`]
+#[derive(Clone, PartialEq, Eq)]
+pub struct Key([u8; 32]);
+
+impl Key {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for Key {
+    fn from(key: [u8; 32]) -> Self {
+        Key(key)
+    }
+}
+
+impl std::fmt::Debug for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+/// Normalized passphrase, kept around until the salt of a file is known
+#[derive(Clone, PartialEq, Eq)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    pub fn new(input: &str) -> Self {
+        Passphrase(passphrase_normalize::normalize(input))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn derive_key(&self, params: &KdfParams) -> Key {
+        derive_key(&self.0, params)
+    }
+}
+
+impl std::fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// Where the key of a recipient slot comes from: a [`Passphrase`], or keys derived from one earlier and kept without it
+pub trait KeySource {
+    /// The key derived with `params`, `None` if it cannot be had, which passes over the slot
+    fn key_for(&self, params: &KdfParams) -> Option<Key>;
+}
+
+impl KeySource for Passphrase {
+    fn key_for(&self, params: &KdfParams) -> Option<Key> {
+        Some(self.derive_key(params))
+    }
+}
@@ -0,0 +1,92 @@
+//! Detached Ed25519 signatures of the published data files.
+//! Keys and signatures are base64 text: a 32 byte seed, a 32 byte public key and a 64 byte signature.
+
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+use ed25519_dalek::{PublicKey, SecretKey, Signature};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    /// the key is not a valid base64 Ed25519 key
+    InvalidKey,
+    /// the signature is not base64 of 64 bytes
+    Malformed,
+    /// the signature does not match the data
+    Mismatch,
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureError::InvalidKey => write!(f, "invalid signing key"),
+            SignatureError::Malformed => write!(f, "malformed signature"),
+            SignatureError::Mismatch => write!(f, "signature does not match"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+/// Secret half of the data signing key, kept out of the repository
+pub struct SigningKey {
+    secret: SecretKey,
+    public: PublicKey,
+}
+
+impl SigningKey {
+    /// `seed` must be freshly random.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let secret = SecretKey::from_bytes(&seed).expect("any 32 bytes are a secret key");
+        let public = PublicKey::from(&secret);
+        SigningKey { secret, public }
+    }
+
+    pub fn decode(text: &str) -> Result<Self, SignatureError> {
+        let seed = base64::decode(text.trim()).map_err(|_| SignatureError::InvalidKey)?;
+        let seed = <[u8; 32]>::try_from(seed.as_slice()).map_err(|_| SignatureError::InvalidKey)?;
+        Ok(SigningKey::from_seed(seed))
+    }
+
+    pub fn encode(&self) -> String {
+        base64::encode(self.secret.as_bytes())
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        VerifyingKey(self.public)
+    }
+
+    /// Sign `data` and encode the signature as base64.
+    pub fn sign(&self, data: &[u8]) -> String {
+        let expanded = ed25519_dalek::ExpandedSecretKey::from(&self.secret);
+        base64::encode(expanded.sign(data, &self.public).to_bytes().as_ref())
+    }
+}
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SigningKey(..)")
+    }
+}
+
+/// Public half of the data signing key, embedded in the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyingKey(PublicKey);
+
+impl VerifyingKey {
+    pub fn decode(text: &str) -> Result<Self, SignatureError> {
+        let key = base64::decode(text.trim()).map_err(|_| SignatureError::InvalidKey)?;
+        PublicKey::from_bytes(&key).map(VerifyingKey).map_err(|_| SignatureError::InvalidKey)
+    }
+
+    pub fn encode(&self) -> String {
+        base64::encode(self.0.as_bytes())
+    }
+
+    /// Check a base64 signature made by [`SigningKey::sign`].
+    pub fn verify(&self, data: &[u8], signature: &str) -> Result<(), SignatureError> {
+        let signature = base64::decode(signature.trim()).map_err(|_| SignatureError::Malformed)?;
+        let signature = Signature::try_from(signature.as_slice()).map_err(|_| SignatureError::Malformed)?;
+        self.0.verify_strict(data, &signature).map_err(|_| SignatureError::Mismatch)
+    }
+}
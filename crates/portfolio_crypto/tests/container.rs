@@ -0,0 +1,55 @@
+//! A sealed box opens only with its key, only as it was written and only in the place it was sealed for.
+
+use portfolio_crypto::{Key, NONCE_SIZE, OpenError, open, seal};
+
+const PLAINTEXT: &[u8] = "山田太郎, 2000-01-01".as_bytes();
+const PATH: &[u8] = b"/0/values/1";
+
+fn key() -> Key {
+    Key::from([7; 32])
+}
+
+fn sealed() -> Vec<u8> {
+    seal(PLAINTEXT, PATH, &key(), [3; NONCE_SIZE])
+}
+
+#[test]
+fn round_trip() {
+    assert_eq!(open(&sealed(), PATH, &key()), Ok(PLAINTEXT.to_vec()));
+    assert_eq!(open(&seal(b"", b"", &key(), [3; NONCE_SIZE]), b"", &key()), Ok(Vec::new()));
+}
+
+#[test]
+fn tampered_tag_is_corrupted() {
+    let mut data = sealed();
+    *data.last_mut().unwrap() ^= 1;
+    assert_eq!(open(&data, PATH, &key()), Err(OpenError::Corrupted));
+}
+
+#[test]
+fn tampered_ciphertext_is_corrupted() {
+    let mut data = sealed();
+    data[NONCE_SIZE + 16] ^= 1;
+    assert_eq!(open(&data, PATH, &key()), Err(OpenError::Corrupted));
+}
+
+#[test]
+fn other_associated_data_is_corrupted() {
+    for other in [&b"/0/values/2"[..], b"/1/values/1", b""] {
+        assert_eq!(open(&sealed(), other, &key()), Err(OpenError::Corrupted), "{:?}", other);
+    }
+    assert_eq!(open(&seal(PLAINTEXT, b"", &key(), [3; NONCE_SIZE]), PATH, &key()), Err(OpenError::Corrupted));
+}
+
+#[test]
+fn wrong_key() {
+    assert_eq!(open(&sealed(), PATH, &Key::from([8; 32])), Err(OpenError::WrongKey));
+}
+
+#[test]
+fn truncated_box_is_corrupted() {
+    let data = sealed();
+    for len in [0, NONCE_SIZE, data.len() / 2, data.len() - 1] {
+        assert_eq!(open(&data[..len], PATH, &key()), Err(OpenError::Corrupted), "length {}", len);
+    }
+}
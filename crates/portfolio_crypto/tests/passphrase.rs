@@ -0,0 +1,44 @@
+//! The same passphrase must yield the same key on the encrypter (native) and in the browser (wasm).
+//! Run natively with `cargo test` and in wasm with `wasm-pack test --node crates/portfolio_crypto`.
+
+use portfolio_crypto::{KdfParams, Passphrase};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const EXPECTED_KEY: &str = "acd0bed261562617516051c8c8e1561204bdd286f347083c89c36264722f12c2";
+
+fn params() -> KdfParams {
+    KdfParams { salt: *b"portfolio-salt-1", iterations: 1000 }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn normalize_variants() {
+    for input in &["やまだ たろう", "ヤマダ　タロウ", "ﾔﾏﾀﾞ ﾀﾛｳ", "やまた\u{3099}たろう"] {
+        assert_eq!(Passphrase::new(input).as_str(), "やまだたろう", "input: {:?}", input);
+    }
+    for input in &["こーひー", "コーヒー", "ｺｰﾋｰ", "こ-ひ-"] {
+        assert_eq!(Passphrase::new(input).as_str(), "こーひー", "input: {:?}", input);
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn only_kana_are_kept() {
+    for input in &["abc", "Taro Yamada", "1234", "山田太郎", "!?　"] {
+        assert_eq!(Passphrase::new(input).as_str(), "", "input: {:?}", input);
+    }
+    assert_eq!(Passphrase::new("Yamada やまだ 2000").as_str(), "やまだ");
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn derive_key_is_stable() {
+    for input in &["やまだたろう", "ヤマダ　タロウ"] {
+        assert_eq!(hex(Passphrase::new(input).derive_key(&params()).as_bytes()), EXPECTED_KEY, "input: {:?}", input);
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Reference to a binary file sealed by `encrypter seal-asset`, such as a photo or a certificate
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssetRef {
+    /// URL of the sealed file
+    pub path: String,
+    /// Description shown while it is locked, and as alt text or link text once opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+}
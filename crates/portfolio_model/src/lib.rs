@@ -0,0 +1,33 @@
+use serde::{Deserialize, Deserializer};
+
+pub mod asset;
+pub mod license;
+pub mod link;
+pub mod profile;
+pub mod protected;
+pub mod qualification;
+pub mod works;
+
+/// Version of the format of the generated data files, raised on every incompatible change.
+/// A file of another version is refused as a whole rather than misread.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Version of a file written before it had a `format_version` field, which is the first format.
+/// Such a file is read as this version without being checked, so the field must stop being optional when [`FORMAT_VERSION`] is raised.
+fn unversioned() -> u32 {
+    1
+}
+
+/// Deserialize a `format_version` field, failing unless it is [`FORMAT_VERSION`]
+fn format_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    supported_version(u32::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+/// `version` if it is [`FORMAT_VERSION`], for a type checking it after the fact rather than with [`format_version`]
+fn supported_version(version: u32) -> Result<u32, String> {
+    if version == FORMAT_VERSION {
+        Ok(version)
+    } else {
+        Err(format!("unsupported format version {}, expected {}", version, FORMAT_VERSION))
+    }
+}
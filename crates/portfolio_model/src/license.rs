@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::protected::Protected;
+
+/// Entry of `license.data.json`, one per crate the site is built from
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct LicenseData {
+    pub name: String,
+    pub version: Option<String>,
+    pub authors: Option<String>,
+    pub repository: Option<String>,
+    pub license: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Contents of `license.data.json`. Nothing in it is sealed, it is in the envelope of the other data files for its `format_version`.
+pub type LicenseDocument = Protected<Vec<LicenseData>>;
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use crate::protected::{MaybeSealed, Protected, Reveal};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum LinkValueData {
+    DisplayOnly(String),
+    WithLink { display: String, link: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum LinkKeyData {
+    NameOnly(String),
+    WithImage { name: String, image: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LinkData {
+    pub key: LinkKeyData,
+    pub value: LinkValueData,
+}
+
+impl Reveal for LinkData {}
+
+/// Contents of `link.data.json`
+pub type LinkDocument = Protected<Vec<MaybeSealed<LinkData>>>;
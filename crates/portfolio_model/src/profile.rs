@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::asset::AssetRef;
+use crate::protected::{Protected, Reveal, RevealError, SealedValue, child_path};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RubyString {
+    pub value: String,
+    pub ruby: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum WrappedString {
+    None,
+    Normal(String),
+    WithRuby(Vec<RubyString>),
+    /// Placeholder of a value that is published only sealed, `{"secret": {"hint": "..."}}`
+    Secret { secret: Secret },
+    /// Sealed binary file shown in place of the value, `{"asset": {"path": "...", "alt": "..."}}`
+    Asset { asset: AssetRef },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Secret {
+    /// Shown in place of the value until it is unlocked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileValueData {
+    pub key: String,
+    pub value: WrappedString,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// JSON encoded real `value`, which is then only a placeholder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed: Option<SealedValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileData {
+    pub category_name: String,
+    pub values: Vec<ProfileValueData>,
+}
+
+/// Contents of `profile.data.json`
+pub type ProfileDocument = Protected<Vec<ProfileData>>;
+
+impl Reveal for ProfileValueData {
+    fn reveal_at<E>(&mut self, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        let plaintext = match &self.sealed {
+            Some(sealed) => open(sealed, path).map_err(RevealError::Open)?,
+            None => None,
+        };
+        match plaintext {
+            Some(plaintext) => {
+                self.value = serde_json::from_slice(&plaintext).map_err(RevealError::Invalid)?;
+                self.sealed = None;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+impl Reveal for ProfileData {
+    fn reveal_at<E>(&mut self, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        self.values.reveal_at(&child_path(path, "values"), open)
+    }
+}
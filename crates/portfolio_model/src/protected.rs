@@ -0,0 +1,133 @@
+use std::convert::TryFrom;
+use std::fmt::Display;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::FORMAT_VERSION;
+
+/// JSON of a value sealed with the data key `key` of the keyring
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SealedValue {
+    pub key: usize,
+    pub data: String,
+}
+
+/// Public data file, optionally with the keyring its sealed parts are opened with.
+/// A file without keyring may also be written as bare `T`, which is read as the first format version.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "ProtectedRepr<T>", bound(deserialize = "T: Deserialize<'de>"))]
+pub struct Protected<T> {
+    /// [`FORMAT_VERSION`] the file was written with, 1 when missing
+    pub format_version: u32,
+    /// Base64 encoded keyring wrapping the data keys of the sealed parts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyring: Option<String>,
+    pub data: T,
+}
+
+impl<T: Default> Default for Protected<T> {
+    fn default() -> Self {
+        Protected { format_version: FORMAT_VERSION, keyring: None, data: T::default() }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProtectedRepr<T> {
+    Document {
+        // checked in `try_from`, a wrong version would otherwise be tried as a bare `T` and only fail as matching neither
+        #[serde(default = "crate::unversioned")]
+        format_version: u32,
+        #[serde(default)]
+        keyring: Option<String>,
+        data: T,
+    },
+    Bare(T),
+}
+
+impl<T> TryFrom<ProtectedRepr<T>> for Protected<T> {
+    type Error = String;
+
+    fn try_from(repr: ProtectedRepr<T>) -> Result<Self, Self::Error> {
+        Ok(match repr {
+            ProtectedRepr::Document { format_version, keyring, data } => Protected { format_version: crate::supported_version(format_version)?, keyring, data },
+            ProtectedRepr::Bare(data) => Protected { format_version: crate::unversioned(), keyring: None, data },
+        })
+    }
+}
+
+/// List entry that is either public or sealed as a whole
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MaybeSealed<T> {
+    Sealed { sealed: SealedValue },
+    Public(T),
+}
+
+impl<T> MaybeSealed<T> {
+    pub fn public(&self) -> Option<&T> {
+        match self {
+            MaybeSealed::Public(value) => Some(value),
+            MaybeSealed::Sealed { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RevealError<E> {
+    Open(E),
+    Invalid(serde_json::Error),
+}
+
+/// JSON pointer of `segment` under `path`. A value is sealed with the pointer of its place in the data,
+/// such as `/1/values/0`, and only opens there.
+pub fn child_path(path: &str, segment: impl Display) -> String {
+    format!("{}/{}", path, segment.to_string().replace('~', "~0").replace('/', "~1"))
+}
+
+/// Data that may contain [`SealedValue`]s
+pub trait Reveal {
+    /// Replace every sealed part `open` returns the plaintext of, and return how many were replaced.
+    /// `open` is given each part with the JSON pointer of its place, see [`child_path`].
+    /// Parts `open` returns `None` for stay sealed.
+    fn reveal<E>(&mut self, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        self.reveal_at("", open)
+    }
+
+    /// [`Reveal::reveal`] for data at `path` within the document
+    fn reveal_at<E>(&mut self, _path: &str, _open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        Ok(0)
+    }
+}
+
+impl<T: Reveal> Reveal for Vec<T> {
+    fn reveal_at<E>(&mut self, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        self.iter_mut().enumerate().try_fold(0, |count, (index, value)| Ok(count + value.reveal_at(&child_path(path, index), open)?))
+    }
+}
+
+impl<T: Reveal> Reveal for Protected<T> {
+    /// The paths are those within `data`, the same in a bare file.
+    fn reveal_at<E>(&mut self, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        self.data.reveal_at(path, open)
+    }
+}
+
+impl<T: Reveal + DeserializeOwned> Reveal for MaybeSealed<T> {
+    fn reveal_at<E>(&mut self, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        match self {
+            MaybeSealed::Public(value) => value.reveal_at(path, open),
+            MaybeSealed::Sealed { sealed } => match open(sealed, path).map_err(RevealError::Open)? {
+                Some(plaintext) => {
+                    let mut value: T = serde_json::from_slice(&plaintext).map_err(RevealError::Invalid)?;
+                    let count = value.reveal_at(path, open)?;
+                    *self = MaybeSealed::Public(value);
+                    Ok(count + 1)
+                }
+                None => Ok(0),
+            },
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use crate::asset::AssetRef;
+use crate::protected::{MaybeSealed, Protected, Reveal, RevealError, SealedValue, child_path};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct QualificationValueData {
+    pub name: String,
+    pub time: String,
+    pub link: Option<String>,
+    /// Sealed scan of the certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<AssetRef>,
+}
+
+impl Reveal for QualificationValueData {}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct QualificationGroupData {
+    pub name: String
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct QualificationData {
+    pub category: QualificationGroupData,
+    pub values: Vec<MaybeSealed<QualificationValueData>>,
+}
+
+impl Reveal for QualificationData {
+    fn reveal_at<E>(&mut self, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        self.values.reveal_at(&child_path(path, "values"), open)
+    }
+}
+
+/// Contents of `qualification.data.json`
+pub type QualificationDocument = Protected<Vec<QualificationData>>;
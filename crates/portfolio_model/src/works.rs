@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::FORMAT_VERSION;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Repository {
+    pub html_url: String,
+    pub name: String,
+    pub homepage: Option<String>,
+    /// index into [`WorksData::languages`] and the bytes of code in it
+    pub language: Vec<(usize, usize)>,
+    pub community_profile: CommunityProfile,
+    /// indexes into [`WorksData::technologies`]
+    pub technology_stacks: Vec<usize>,
+    /// indexes into [`WorksData::repositories`]
+    pub related_repositories: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CommunityProfile {
+    pub health_percentage: usize,
+    pub description: Option<String>,
+    pub documentation: Option<String>,
+    pub files: CommunityProfileFiles,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CommunityProfileFiles {
+    pub license: Option<HashMap<String, Option<String>>>,
+    pub readme: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Language {
+    pub name: String,
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TechnologyStack {
+    pub name: String,
+    pub link: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Contents of `works.data.json`, written by `works_generator`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct WorksData {
+    /// [`FORMAT_VERSION`] the file was written with, 1 when missing
+    #[serde(default = "crate::unversioned", deserialize_with = "crate::format_version")]
+    pub format_version: u32,
+    pub repositories: Vec<Repository>,
+    /// indexed by [`Repository::language`]
+    pub languages: Vec<Language>,
+    /// indexed by [`Repository::technology_stacks`]
+    pub technologies: Vec<TechnologyStack>,
+}
+
+impl Default for WorksData {
+    fn default() -> Self {
+        WorksData { format_version: FORMAT_VERSION, repositories: Vec::new(), languages: Vec::new(), technologies: Vec::new() }
+    }
+}
@@ -0,0 +1,141 @@
+//! Every data file must read back unchanged after being written, since the tools rewrite what the site reads.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use portfolio_model::FORMAT_VERSION;
+use portfolio_model::license::LicenseDocument;
+use portfolio_model::link::LinkDocument;
+use portfolio_model::profile::ProfileDocument;
+use portfolio_model::qualification::QualificationDocument;
+use portfolio_model::works::{CommunityProfile, CommunityProfileFiles, Language, Repository, TechnologyStack, WorksData};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+fn static_file(name: &str) -> Vec<u8> {
+    let path = format!("{}/../../static/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("failed to read {}: {}", path, e))
+}
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(data: &T) {
+    let json = serde_json::to_string(data).unwrap();
+    let read: T = serde_json::from_str(&json).unwrap_or_else(|e| panic!("failed to read back {}: {}", json, e));
+    assert_eq!(&read, data);
+}
+
+fn round_trip_file<T: Serialize + DeserializeOwned + PartialEq + Debug>(name: &str) {
+    let data: T = serde_json::from_slice(&static_file(name)).unwrap_or_else(|e| panic!("failed to read {}: {}", name, e));
+    round_trip(&data);
+}
+
+#[test]
+fn link_data() {
+    round_trip_file::<LinkDocument>("link.data.json");
+}
+
+#[test]
+fn qualification_data() {
+    round_trip_file::<QualificationDocument>("qualification.data.json");
+}
+
+#[test]
+fn profile_data() {
+    round_trip_file::<ProfileDocument>("profile.data.json");
+}
+
+#[test]
+fn license_data() {
+    round_trip_file::<LicenseDocument>("license.data.json");
+}
+
+fn works() -> WorksData {
+    let readme = vec![("url".to_string(), "https://api.github.com/repos/White-Green/Portfolio/readme".to_string())].into_iter().collect::<HashMap<_, _>>();
+    WorksData {
+        format_version: FORMAT_VERSION,
+        repositories: vec![
+            Repository {
+                html_url: "https://github.com/White-Green/Portfolio".to_string(),
+                name: "Portfolio".to_string(),
+                homepage: Some("https://white-green.github.io/".to_string()),
+                language: vec![(0, 1200), (1, 300)],
+                community_profile: CommunityProfile {
+                    health_percentage: 42,
+                    description: Some("White-Green's Portfolio.".to_string()),
+                    documentation: None,
+                    files: CommunityProfileFiles { license: None, readme: Some(readme) },
+                },
+                technology_stacks: vec![0],
+                related_repositories: vec![1],
+            },
+            Repository {
+                html_url: "https://github.com/White-Green/White-Green.github.io".to_string(),
+                name: "White-Green.github.io".to_string(),
+                homepage: None,
+                language: vec![],
+                community_profile: CommunityProfile {
+                    health_percentage: 0,
+                    description: Some("".to_string()),
+                    documentation: None,
+                    files: CommunityProfileFiles { license: Some(HashMap::new()), readme: None },
+                },
+                technology_stacks: vec![],
+                related_repositories: vec![],
+            },
+        ],
+        languages: vec![
+            Language { name: "Rust".to_string(), link: Some("https://www.rust-lang.org/".to_string()) },
+            Language { name: "TypeScript".to_string(), link: None },
+        ],
+        technologies: vec![
+            TechnologyStack { name: "Yew".to_string(), link: None, description: Some("Rust / Wasm framework".to_string()) },
+        ],
+    }
+}
+
+#[test]
+fn works_data() {
+    round_trip(&works());
+    round_trip(&WorksData::default());
+}
+
+#[test]
+fn works_data_of_another_version_is_refused() {
+    let mut json = serde_json::to_value(works()).unwrap();
+    json["format_version"] = (FORMAT_VERSION + 1).into();
+    assert!(serde_json::from_value::<WorksData>(json).is_err());
+}
+
+#[test]
+fn works_data_without_version_is_the_first_version() {
+    let mut json = serde_json::to_value(works()).unwrap();
+    json.as_object_mut().unwrap().remove("format_version");
+    let data: WorksData = serde_json::from_value(json).unwrap();
+    assert_eq!(data.format_version, 1);
+    assert_eq!(data, works());
+}
+
+#[test]
+fn bare_document_is_written_with_data_field() {
+    let document: LinkDocument = serde_json::from_str(r#"[{"key": "GitHub", "value": "White-Green"}]"#).unwrap();
+    assert_eq!(document.keyring, None);
+    assert_eq!(document.format_version, 1);
+    let json = serde_json::to_value(&document).unwrap();
+    assert!(json.get("data").is_some());
+    assert_eq!(json["format_version"], FORMAT_VERSION);
+    round_trip(&document);
+}
+
+#[test]
+fn document_without_version_is_the_first_version() {
+    let document: LicenseDocument = serde_json::from_str(r#"{"data": [{"name": "serde", "version": "1.0.0", "authors": null, "repository": null, "license": null, "description": null}]}"#).unwrap();
+    assert_eq!(document.format_version, 1);
+    assert_eq!(document.data[0].name, "serde");
+}
+
+#[test]
+fn document_of_another_version_is_refused() {
+    let json = format!(r#"{{"format_version": {}, "data": [{{"key": "GitHub", "value": "White-Green"}}]}}"#, FORMAT_VERSION + 1);
+    let e = serde_json::from_str::<LinkDocument>(&json).unwrap_err();
+    assert!(e.to_string().contains("unsupported format version"), "{}", e);
+    assert!(serde_json::from_str::<LicenseDocument>(&json.replace(r#""key": "GitHub", "value": "White-Green""#, r#""name": "serde""#)).is_err());
+}
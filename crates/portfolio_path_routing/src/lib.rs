@@ -0,0 +1,4 @@
+//! The site with the `path-routing` feature. The bundler only builds the default features of the crate it is given,
+//! so `static/index.paths.ts` gives it this one, which turns the feature on as a dependency, and the exports are those of `portfolio`.
+
+pub use portfolio::run;
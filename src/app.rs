@@ -1,19 +1,54 @@
+use std::time::Duration;
+
+use portfolio_crypto::{KeySource, OpenError};
 use yew::prelude::*;
-use yew_router::{prelude::*, route::Route};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yew::utils::window;
+use yew_router::{prelude::*, route::Route, switch::AllowMissing};
+
+use crate::components::{footer::footer, nav::Nav, unlock_dialog::UnlockDialog};
+use crate::protected::{DecryptError, PROTECTED_FILES, check_key, recipient_params};
+use crate::routes::{AppRoute, home::Home, license::License, links::Links, not_found::NotFound, profile::Profile, qualifications::Qualifications, works::Works};
+use crate::session::{Session, UnlockKey, take_link_key};
+use crate::store::{self, Subscription};
 
-use crate::components::{footer::footer, nav::Nav};
-use crate::routes::{AppRoute, home::Home, license::License, links::Links, profile::Profile, qualifications::Qualifications, works::Works};
+/// Wait before each key derivation, which blocks the page for a while, so that the page is painted in between
+const DERIVE_DELAY: Duration = Duration::from_millis(20);
 
 /// Root component
 pub(crate) struct App {
     current_route: AppRoute,
     link: ComponentLink<Self>,
-    key: Option<aes::Key>,
+    session: Session,
+    unlock_level: Option<String>,
+    unlock_dialog: bool,
+    /// key of the submitted passphrase while it is checked, see [`check_key`], the session keeps its key until it opens
+    candidate: Option<UnlockKey>,
+    unlock_error: Option<String>,
+    /// the protected files while the key waits for them to be checked against
+    key_check: Vec<Subscription>,
+    /// the next key derivation of the check, see [`App::check_key`]
+    derive_task: Option<TimeoutTask>,
+    /// a data file has changed since the copy on the page was kept
+    update_available: bool,
 }
 
 pub(crate) enum AppMessage {
     ChangeCurrentRoute(AppRoute),
-    ChangeKey(aes::Key),
+    ChangeKey(portfolio_crypto::Passphrase),
+    /// a file the key is to be checked against has been loaded
+    CheckKey,
+    /// derive the key for the next recipient slot to check
+    DeriveKey,
+    Unlocked(Result<String, DecryptError>),
+    RequestUnlock,
+    CloseUnlock,
+    Lock,
+    Remember(bool),
+    IdleCheck,
+    KeyDerived,
+    UpdateAvailable,
+    Reload,
 }
 
 impl Component for App {
@@ -21,7 +56,27 @@ impl Component for App {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        App { current_route: AppRoute::Home, link, key: None }
+        let session = Session::new(link.callback(|_| AppMessage::IdleCheck), link.callback(|_| AppMessage::KeyDerived));
+        store::on_update_available(link.callback(|()| AppMessage::UpdateAvailable));
+        let mut app = App {
+            current_route: AppRoute::Home,
+            link,
+            session,
+            unlock_level: None,
+            unlock_dialog: false,
+            candidate: None,
+            unlock_error: None,
+            key_check: Vec::new(),
+            derive_task: None,
+            update_available: false,
+        };
+        // before the router sees the route, the key is not part of it
+        if let Some(key) = take_link_key() {
+            app.link.send_message(AppMessage::ChangeKey(key));
+        }
+        // a remembered key, for its label
+        app.check_key();
+        app
     }
 
     fn change(&mut self, _: Self::Properties) -> ShouldRender {
@@ -31,28 +86,133 @@ impl Component for App {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             AppMessage::ChangeCurrentRoute(route) => {
-                if route != self.current_route {
-                    self.current_route = route;
+                let changed = route != self.current_route;
+                self.current_route = route;
+                if let Some(key) = take_link_key() {
+                    self.link.send_message(AppMessage::ChangeKey(key));
+                }
+                changed
+            }
+            AppMessage::ChangeKey(key) => {
+                if self.session.throttle().retry_at().is_some() {
+                    // a shared link may bring a key while the dialog is closed
+                    self.unlock_dialog = true;
+                    self.unlock_error = Some("too many incorrect passphrases, wait before trying again".to_string());
+                    return true;
+                }
+                self.candidate = Some(self.session.candidate(key));
+                self.unlock_error = None;
+                self.derive_task = None;
+                self.check_key();
+                true
+            }
+            AppMessage::CheckKey => self.check_key(),
+            AppMessage::DeriveKey => {
+                self.derive_task = None;
+                if let Some(key) = self.checked_key() {
+                    if let Some(params) = key.underived(&recipient_params()) {
+                        key.key_for(params);
+                    }
+                }
+                self.check_key()
+            }
+            AppMessage::Unlocked(Ok(level)) => {
+                if let Some(key) = self.candidate.take() {
+                    self.session.unlock(key);
+                    self.session.unlock_succeeded();
+                    self.unlock_dialog = false;
+                }
+                self.unlock_level = Some(level);
+                true
+            }
+            AppMessage::Unlocked(Err(e)) => {
+                // a submitted key is dropped and the session keeps the key it had, a typo does not lock it
+                let submitted = self.candidate.take().is_some();
+                let wrong_key = e == DecryptError::Open(OpenError::WrongKey);
+                if submitted && wrong_key {
+                    self.session.unlock_failed();
+                }
+                // a remembered key is dropped, the files may have been sealed again since
+                if !submitted && (wrong_key || e == DecryptError::NotYetValid || e == DecryptError::Expired) {
+                    self.session.lock();
+                    self.unlock_level = None;
+                }
+                if submitted {
+                    self.unlock_dialog = true;
+                    self.unlock_error = Some(if wrong_key { "incorrect passphrase".to_string() } else { e.to_string() });
+                }
+                true
+            }
+            AppMessage::RequestUnlock => {
+                self.unlock_dialog = true;
+                self.unlock_error = None;
+                true
+            }
+            AppMessage::CloseUnlock => {
+                // a key being checked is still checked, and still counts against the throttle
+                self.unlock_dialog = false;
+                true
+            }
+            AppMessage::Lock => {
+                self.key_check.clear();
+                self.derive_task = None;
+                self.candidate = None;
+                self.session.lock() | self.unlock_level.take().is_some()
+            }
+            AppMessage::Remember(remember) => self.session.set_remember(remember),
+            AppMessage::IdleCheck => {
+                if self.session.lock_if_idle() {
+                    self.key_check.clear();
+                    self.derive_task = None;
+                    self.candidate = None;
+                    self.unlock_level = None;
                     true
                 } else {
                     false
                 }
             }
-            AppMessage::ChangeKey(key) => {
-                self.key = Some(key);
-                true
+            AppMessage::KeyDerived => {
+                self.session.key_derived();
+                false
+            }
+            AppMessage::UpdateAvailable => !std::mem::replace(&mut self.update_available, true),
+            AppMessage::Reload => {
+                if let Err(e) = window().location().reload() {
+                    log::error!("failed to reload: {:?}", e);
+                }
+                false
             }
         }
     }
 
     fn view(&self) -> Html {
         let callback = self.link.callback(|route| AppMessage::ChangeCurrentRoute(route));
-        let key_callback = self.link.callback(|key| AppMessage::ChangeKey(key));
-        let key = self.key.clone();
+        let request_unlock = self.link.callback(|_| AppMessage::RequestUnlock);
+        let key = self.session.key().cloned();
         html! {
             <>
                 <header>
-                    <Nav current_route=self.current_route.clone() key_callback=key_callback/>
+                    <Nav current_route=self.current_route.clone()
+                        unlock_callback=request_unlock.clone()
+                        lock_callback=self.link.callback(|_| AppMessage::Lock)
+                        remember_callback=self.link.callback(AppMessage::Remember)
+                        unlocked=self.session.is_unlocked()
+                        remember=self.session.remember()
+                        unlock_level=self.unlock_level.clone()/>
+                    {
+                        if self.update_available {
+                            html! {
+                                <div class="alert alert-info rounded-0 mb-0 text-center" role="alert">
+                                    { "A new version of this page is available." }
+                                    <button type="button" class="btn btn-link btn-sm align-baseline ml-2 p-0" onclick=self.link.callback(|_| AppMessage::Reload)>
+                                        { "reload" }
+                                    </button>
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
                 </header>
                 <main>
                     <div class="container">
@@ -63,13 +223,14 @@ impl Component for App {
                                 html!{
                                     <>
                                         {
-                                            match switch {
+                                            match &switch {
                                                 AppRoute::Home => html!{ <Home /> },
-                                                AppRoute::Profile => html!{ <Profile encrypt_key=key.clone() /> },
-                                                AppRoute::Qualifications => html!{ <Qualifications /> },
-                                                AppRoute::Links => html!{ <Links /> },
-                                                AppRoute::Works => html!{ <Works /> },
+                                                AppRoute::Profile => html!{ <Profile encrypt_key=key.clone() request_unlock=request_unlock.clone() /> },
+                                                AppRoute::Qualifications => html!{ <Qualifications encrypt_key=key.clone() request_unlock=request_unlock.clone() /> },
+                                                AppRoute::Links => html!{ <Links encrypt_key=key.clone() request_unlock=request_unlock.clone() /> },
+                                                AppRoute::Works(AllowMissing(selection)) => html!{ <Works selection=selection.clone() /> },
                                                 AppRoute::License => html!{},
+                                                AppRoute::NotFound(path) => html!{ <NotFound path=path.clone() /> },
                                             }
                                         }
                                         <License show={switch == AppRoute::License}/>
@@ -78,7 +239,7 @@ impl Component for App {
                             } )
                             redirect = Router::redirect(|route: Route<()>| {
                                 log::info!("redirect");
-                                AppRoute::Home
+                                AppRoute::not_found(&route.route)
                             })
                         />
                     </div>
@@ -86,7 +247,60 @@ impl Component for App {
                 <footer class="footer mt-auto py-3">
                     { footer() }
                 </footer>
+                <UnlockDialog show=self.unlock_dialog
+                    checking=self.candidate.is_some()
+                    error=self.unlock_error.clone()
+                    retry_at=self.session.throttle().retry_at()
+                    submit_callback=self.link.callback(AppMessage::ChangeKey)
+                    close_callback=self.link.callback(|_| AppMessage::CloseUnlock)/>
             </>
         }
     }
 }
+
+impl App {
+    /// Check the submitted key, or else the key of the session, against the keyrings, fetching them first if need be, see [`check_key`].
+    /// Only the keys derived so far are tried, and each missing one is derived in an [`AppMessage::DeriveKey`] of its own,
+    /// once per distinct [`KdfParams`](portfolio_crypto::KdfParams), so that the page shows the check going on.
+    /// The result comes back as [`AppMessage::Unlocked`].
+    fn check_key(&mut self) -> ShouldRender {
+        let key = match self.checked_key() {
+            Some(key) => key,
+            None => {
+                self.key_check.clear();
+                self.derive_task = None;
+                return false;
+            }
+        };
+        let result = check_key(&key.derived());
+        // a slot may open with a key not derived yet
+        let derive = !matches!(result, Some(Ok(_))) && key.underived(&recipient_params()).is_some();
+        if derive && self.derive_task.is_none() {
+            self.derive_task = Some(TimeoutService::spawn(DERIVE_DELAY, self.link.callback(|()| AppMessage::DeriveKey)));
+        }
+        match result {
+            Some(result) if !derive => {
+                self.key_check.clear();
+                self.derive_task = None;
+                self.update(AppMessage::Unlocked(result))
+            }
+            Some(_) => false,
+            None => {
+                self.subscribe_key_check();
+                false
+            }
+        }
+    }
+
+    fn checked_key(&self) -> Option<&UnlockKey> {
+        self.candidate.as_ref().or_else(|| self.session.key())
+    }
+
+    fn subscribe_key_check(&mut self) {
+        if self.key_check.is_empty() {
+            self.key_check = PROTECTED_FILES.iter()
+                .map(|&(addr, signature_addr)| store::subscribe(addr, Some(signature_addr), self.link.callback(|()| AppMessage::CheckKey)))
+                .collect();
+        }
+    }
+}
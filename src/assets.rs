@@ -0,0 +1,3 @@
+//! Served URL of every file under `static/`, named as parcel names it. Generated by `build.rs`.
+
+include!(concat!(env!("OUT_DIR"), "/assets.rs"));
@@ -0,0 +1,58 @@
+//! Copies of the fetched files kept in `localStorage`, so that the next visit shows them at once
+//! while the [store](crate::store) asks the server whether they are still current.
+
+use serde::{Deserialize, Serialize};
+use yew::format::Json;
+use yew::services::fetch::Response;
+use yew::services::storage::{Area, StorageService};
+
+const KEY_PREFIX: &str = "portfolio.cache:";
+/// Bigger files are left to the service worker, `localStorage` holds a few megabytes only
+const MAX_SIZE: usize = 512 * 1024;
+
+/// What the server identified a response with, sent back with the next request for the same file
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Validators {
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+}
+
+impl Validators {
+    pub(crate) fn of<T>(response: &Response<T>) -> Self {
+        let header = |name: &str| response.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+        Validators { etag: header("ETag"), last_modified: header("Last-Modified") }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFile {
+    validators: Validators,
+    body: String,
+}
+
+fn storage() -> Option<StorageService> {
+    StorageService::new(Area::Local)
+        .map_err(|e| log::warn!("localStorage is not available: {}", e))
+        .ok()
+}
+
+/// The copy of `addr` saved by an earlier visit
+pub(crate) fn load(addr: &str) -> Option<(Vec<u8>, Validators)> {
+    let Json(file) = storage()?.restore::<Json<Result<CachedFile, anyhow::Error>>>(&format!("{}{}", KEY_PREFIX, addr));
+    let file = file.ok()?;
+    Some((file.body.into_bytes(), file.validators))
+}
+
+/// Keep `data` for the next visit, unless it is binary or too big
+pub(crate) fn save(addr: &str, data: &[u8], validators: &Validators) {
+    if data.len() > MAX_SIZE {
+        return;
+    }
+    let body = match std::str::from_utf8(data) {
+        Ok(body) => body.to_string(),
+        Err(_) => return,
+    };
+    if let Some(mut storage) = storage() {
+        storage.store(&format!("{}{}", KEY_PREFIX, addr), Json(&CachedFile { validators: validators.clone(), body }));
+    }
+}
@@ -1,5 +1,7 @@
 pub(crate) mod nav;
 pub(crate) mod footer;
+pub(crate) mod sealed_asset;
+pub(crate) mod unlock_dialog;
 
 #[macro_export]
 macro_rules! pure_component (
@@ -1,11 +1,10 @@
-use std::convert::TryInto;
-
-use sha3::Digest;
 use yew::prelude::*;
-use yew::utils::window;
 use yew_router::prelude::*;
+use yew_router::switch::AllowMissing;
 
+use crate::assets;
 use crate::routes::AppRoute;
+use crate::store;
 
 /// Nav component
 pub(crate) struct Nav {
@@ -16,12 +15,66 @@ pub(crate) struct Nav {
 #[derive(Debug, PartialEq, Clone, Properties)]
 pub(crate) struct NavProps {
     pub(crate) current_route: AppRoute,
-    pub(crate) key_callback: Callback<aes::Key>,
+    pub(crate) unlock_callback: Callback<()>,
+    pub(crate) lock_callback: Callback<()>,
+    pub(crate) remember_callback: Callback<bool>,
+    #[prop_or_default]
+    pub(crate) unlocked: bool,
+    #[prop_or_default]
+    pub(crate) remember: bool,
+    #[prop_or_default]
+    pub(crate) unlock_level: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) enum NavMessage {
-    TryUnlock
+    TryUnlock,
+    Prefetch(AppRoute),
+}
+
+impl Nav {
+    /// Lock state indicator, the unlock or lock button and the "remember" switch
+    fn lock_view(&self) -> Html {
+        let remember = self.props.remember;
+        let remember_switch = html! {
+            <li class="nav-item">
+              <div class="custom-control custom-switch navbar-text">
+                <input type="checkbox" class="custom-control-input" id="rememberUnlock" checked=remember
+                  onclick=self.props.remember_callback.reform(move |_| !remember)/>
+                <label class="custom-control-label" for="rememberUnlock">{"remember in this tab"}</label>
+              </div>
+            </li>
+        };
+        if self.props.unlocked {
+            let state = match &self.props.unlock_level {
+                Some(level) => format!("\u{1f513} unlocked: {}", level),
+                None => "\u{1f513} unlocked".to_string(),
+            };
+            html! {
+                <>
+                    <li class="nav-item">
+                      <span class="navbar-text badge badge-success">{ state }</span>
+                    </li>
+                    { remember_switch }
+                    <li class="nav-item">
+                      <a class="btn btn-outline-secondary nav-link" onclick=self.props.lock_callback.reform(|_| ())>{"lock"}</a>
+                    </li>
+                </>
+            }
+        } else {
+            html! {
+                <>
+                    <li class="nav-item">
+                      <span class="navbar-text badge badge-secondary">{"\u{1f512} locked"}</span>
+                    </li>
+                    { remember_switch }
+                    <li class="nav-item">
+                      <a class="btn btn-primary nav-link" onclick=self.link.callback(|_|NavMessage::TryUnlock)>{"unlock"}</a>
+                    </li>
+                </>
+            }
+        }
+    }
 }
 
 impl Component for Nav {
@@ -44,13 +97,12 @@ impl Component for Nav {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             NavMessage::TryUnlock => {
-                let name = window().prompt_with_message("本サイト所有者の氏名をひらがなで入力してください").expect("error in prompt");
-                if let Some(name) = name {
-                    let name = regex::Regex::new("[^ぁ-ゖ]").unwrap().replace_all(&name, "");
-                    log::info!("{}", name);
-                    let key = sha3::Sha3_256::digest(name.as_bytes()).to_vec().try_into().unwrap();
-                    let key = aes::Key::AES256(key);
-                    self.props.key_callback.emit(key);
+                self.props.unlock_callback.emit(());
+                false
+            }
+            NavMessage::Prefetch(route) => {
+                for (addr, signature_addr) in route.data_files() {
+                    store::prefetch(addr, *signature_addr);
                 }
                 false
             }
@@ -58,17 +110,18 @@ impl Component for Nav {
     }
 
     fn view(&self) -> Html {
-        let navbar_links = [AppRoute::Home, AppRoute::Profile, AppRoute::Works, AppRoute::Qualifications, AppRoute::Links]
+        let navbar_links = [AppRoute::Home, AppRoute::Profile, AppRoute::Works(AllowMissing(None)), AppRoute::Qualifications, AppRoute::Links]
             .iter()
-            .map(|route| if route == &self.props.current_route {
+            .map(|route| if route == &self.props.current_route.page() {
                 html! {
                     <li class="nav-item active">
                         <RouterAnchor<AppRoute> route=route.clone() classes="nav-link" >{ route.to_string() } <span class="sr-only">{"(current)"}</span></RouterAnchor<AppRoute>>
                     </li>
                 }
             } else {
+                let prefetch = route.clone();
                 html! {
-                    <li class="nav-item">
+                    <li class="nav-item" onmouseenter=self.link.callback(move |_| NavMessage::Prefetch(prefetch.clone()))>
                         <RouterAnchor<AppRoute> route=route.clone() classes="nav-link" >{ route.to_string() }</RouterAnchor<AppRoute>>
                     </li>
                 }
@@ -76,7 +129,7 @@ impl Component for Nav {
         html! {
             <nav class="navbar navbar-expand-md navbar-light bg-light">
                 <RouterAnchor<AppRoute> route=AppRoute::Home classes="navbar-brand" >
-                    <img src="./icon.bac3c665.svg" width="30" height="30" class="rotate"/>
+                    <img src=assets::ICON_SVG width="30" height="30" class="rotate"/>
                     { "Portfolio" }
                 </RouterAnchor<AppRoute>>
                 <button class="navbar-toggler" type="button" data-toggle="collapse" data-target="#navbarNav" aria-controls="navbarNav" aria-expanded="false" aria-label="Toggle navigation">
@@ -85,9 +138,7 @@ impl Component for Nav {
                 <div class="collapse navbar-collapse" id="navbarNav">
                   <ul class="navbar-nav">
                     {for navbar_links}
-                    <li class="nav-item">
-                      <a class="btn btn-primary nav-link" onclick=self.link.callback(|_|NavMessage::TryUnlock)>{"unlock"}</a>
-                    </li>
+                    { self.lock_view() }
                   </ul>
                 </div>
             </nav>
@@ -0,0 +1,172 @@
+use portfolio_crypto::SealedAsset;
+use portfolio_model::asset::AssetRef;
+use wasm_bindgen::JsValue;
+use web_sys::{Blob, BlobPropertyBag, Url};
+use yew::prelude::*;
+
+use crate::protected::{DecryptError, check_validity};
+use crate::session::UnlockKey;
+use crate::store::{self, Subscription};
+
+/// Object URL of decrypted content, revoked when dropped
+struct ObjectUrl(String);
+
+impl ObjectUrl {
+    fn new(content: &[u8], mime: &str) -> Result<Self, JsValue> {
+        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(content));
+        let blob = Blob::new_with_u8_array_sequence_and_options(&parts, BlobPropertyBag::new().type_(mime))?;
+        Url::create_object_url_with_blob(&blob).map(ObjectUrl)
+    }
+}
+
+impl Drop for ObjectUrl {
+    fn drop(&mut self) {
+        let _ = Url::revoke_object_url(&self.0);
+    }
+}
+
+/// Sealed binary file, taken from the store once a key is given and shown through an object URL until the key is gone
+pub(crate) struct SealedAssetView {
+    props: SealedAssetProps,
+    link: ComponentLink<Self>,
+    asset: Option<SealedAsset>,
+    url: Option<ObjectUrl>,
+    error: Option<String>,
+    subscription: Option<Subscription>,
+}
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub(crate) struct SealedAssetProps {
+    pub(crate) asset: AssetRef,
+    #[prop_or_default]
+    pub(crate) encrypt_key: Option<UnlockKey>,
+    #[prop_or_default]
+    pub(crate) request_unlock: Callback<()>,
+}
+
+pub(crate) enum SealedAssetMessage {
+    Fetched(Option<SealedAsset>),
+}
+
+impl SealedAssetView {
+    /// The sealed file if the store has it, `Some(None)` if it could not be loaded
+    fn stored(path: &str) -> Option<Option<SealedAsset>> {
+        let asset = store::bytes(path)?.map_err(|e| e.to_string())
+            .and_then(|data| SealedAsset::parse(&data).map_err(|e| e.to_string()));
+        Some(asset.map_err(|e| log::error!("error in fetching {}: {}", path, e)).ok())
+    }
+
+    fn fetch(&mut self) {
+        let path = self.props.asset.path.clone();
+        let callback = self.link.callback(move |()| SealedAssetMessage::Fetched(Self::stored(&path).flatten()));
+        self.subscription = Some(store::subscribe(&self.props.asset.path, None, callback));
+        match Self::stored(&self.props.asset.path) {
+            Some(Some(asset)) => self.asset = Some(asset),
+            Some(None) => self.loading_failed(),
+            None => {}
+        }
+    }
+
+    fn loading_failed(&mut self) {
+        // subscribing again on the next key fetches it again
+        self.subscription = None;
+        self.error = Some("unable to load".to_string());
+    }
+
+    /// Decrypt the fetched asset with the current key, or fetch it first
+    fn open(&mut self) {
+        self.url = None;
+        self.error = None;
+        if self.props.encrypt_key.is_none() {
+            return;
+        }
+        if self.asset.is_none() && self.subscription.is_none() {
+            self.fetch();
+        }
+        let (key, asset) = match (&self.props.encrypt_key, &self.asset) {
+            (Some(key), Some(asset)) => (key, asset),
+            _ => return,
+        };
+        let content = asset.keyring.recipient_keys(key)
+            .map_err(DecryptError::Open)
+            .and_then(|keys| {
+                check_validity(&keys)?;
+                asset.open_with(&keys).map_err(DecryptError::Open)
+            });
+        match content {
+            Ok(content) => match ObjectUrl::new(&content, &asset.mime) {
+                Ok(url) => self.url = Some(url),
+                Err(e) => {
+                    log::error!("error in creating object URL for {}: {:?}", self.props.asset.path, e);
+                    self.error = Some("unable to show".to_string());
+                }
+            },
+            Err(e) => {
+                log::error!("error in unlocking {}: {}", self.props.asset.path, e);
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+impl Component for SealedAssetView {
+    type Message = SealedAssetMessage;
+    type Properties = SealedAssetProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut view = SealedAssetView { props, link, asset: None, url: None, error: None, subscription: None };
+        view.open();
+        view
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            SealedAssetMessage::Fetched(Some(asset)) => {
+                self.asset = Some(asset);
+                self.open();
+            }
+            SealedAssetMessage::Fetched(None) => self.loading_failed(),
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props == props {
+            return false;
+        }
+        if self.props.asset != props.asset {
+            self.asset = None;
+            self.subscription = None;
+        }
+        self.props = props;
+        self.open();
+        true
+    }
+
+    fn view(&self) -> Html {
+        let alt = self.props.asset.alt.as_deref();
+        match (&self.url, &self.asset, &self.error) {
+            (Some(url), Some(asset), _) if asset.mime.starts_with("image/") => html! {
+                <img class="img-fluid revealed-value" src=url.0.clone() alt=alt.unwrap_or_default()/>
+            },
+            (Some(url), _, _) => html! {
+                <a class="revealed-value" href=url.0.clone() target="_blank" rel="noopener">{ alt.unwrap_or("open") }</a>
+            },
+            (None, _, Some(error)) => html! {
+                <span class="secret-value">{ format!("{}: {}", alt.unwrap_or("file"), error) }</span>
+            },
+            (None, _, None) if self.props.encrypt_key.is_some() => html! {
+                <span class="secret-value">{ format!("{}: decrypting...", alt.unwrap_or("file")) }</span>
+            },
+            (None, _, None) => html! {
+                <span class="secret-value">
+                    <span class="mr-2" aria-hidden="true">{"\u{1f512}"}</span>
+                    { alt.unwrap_or("secret") }
+                    <button type="button" class="btn btn-link btn-sm align-baseline ml-2 p-0" onclick=self.props.request_unlock.reform(|_| ())>
+                        { "unlock to view" }
+                    </button>
+                </span>
+            },
+        }
+    }
+}
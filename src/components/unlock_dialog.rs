@@ -0,0 +1,211 @@
+use std::time::Duration;
+
+use portfolio_crypto::Passphrase;
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+use yew::services::interval::IntervalTask;
+use yew::services::IntervalService;
+
+/// Failures allowed before [`Throttle`] starts making the user wait
+const FREE_ATTEMPTS: u32 = 3;
+const FIRST_WAIT: Duration = Duration::from_secs(5);
+const MAX_WAIT: Duration = Duration::from_secs(5 * 60);
+
+/// Backoff after repeated wrong passphrases.
+/// Only a speed bump for people guessing by hand, PBKDF2 is what makes offline guessing expensive.
+/// The [session](crate::session::Session) keeps it in `sessionStorage`, so that reloading the tab does not reset it.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Throttle {
+    failures: u32,
+    retry_at: Option<f64>,
+}
+
+impl Throttle {
+    pub(crate) fn fail(&mut self) {
+        self.failures += 1;
+        if self.failures >= FREE_ATTEMPTS {
+            let wait = FIRST_WAIT.as_millis() as f64 * 2f64.powi((self.failures - FREE_ATTEMPTS) as i32);
+            self.retry_at = Some(js_sys::Date::now() + wait.min(MAX_WAIT.as_millis() as f64));
+        }
+    }
+
+    pub(crate) fn succeed(&mut self) {
+        *self = Throttle::default();
+    }
+
+    /// Time in milliseconds since the epoch before which no attempt is accepted
+    pub(crate) fn retry_at(&self) -> Option<f64> {
+        self.retry_at.filter(|&at| at > js_sys::Date::now())
+    }
+}
+
+/// Modal asking for the unlock passphrase
+pub(crate) struct UnlockDialog {
+    props: UnlockDialogProps,
+    link: ComponentLink<Self>,
+    input: String,
+    show_input: bool,
+    countdown: Option<IntervalTask>,
+}
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub(crate) struct UnlockDialogProps {
+    pub(crate) show: bool,
+    pub(crate) submit_callback: Callback<Passphrase>,
+    pub(crate) close_callback: Callback<()>,
+    /// waiting for the result of the submitted passphrase
+    #[prop_or_default]
+    pub(crate) checking: bool,
+    #[prop_or_default]
+    pub(crate) error: Option<String>,
+    #[prop_or_default]
+    pub(crate) retry_at: Option<f64>,
+}
+
+pub(crate) enum UnlockDialogMessage {
+    Input(String),
+    ToggleShowInput,
+    Submit,
+    Close,
+    Tick,
+}
+
+impl UnlockDialog {
+    fn seconds_to_wait(&self) -> Option<u64> {
+        self.props.retry_at
+            .map(|at| ((at - js_sys::Date::now()) / 1000.0).ceil())
+            .filter(|&seconds| seconds > 0.0)
+            .map(|seconds| seconds as u64)
+    }
+
+    fn update_countdown(&mut self) {
+        self.countdown = if self.seconds_to_wait().is_some() {
+            Some(IntervalService::spawn(Duration::from_secs(1), self.link.callback(|_| UnlockDialogMessage::Tick)))
+        } else {
+            None
+        };
+    }
+}
+
+impl Component for UnlockDialog {
+    type Message = UnlockDialogMessage;
+    type Properties = UnlockDialogProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut dialog = UnlockDialog { props, link, input: String::new(), show_input: false, countdown: None };
+        dialog.update_countdown();
+        dialog
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            UnlockDialogMessage::Input(input) => {
+                self.input = input;
+                true
+            }
+            UnlockDialogMessage::ToggleShowInput => {
+                self.show_input = !self.show_input;
+                true
+            }
+            UnlockDialogMessage::Submit => {
+                let passphrase = Passphrase::new(&self.input);
+                // input without kana is read as nothing, which is not worth an attempt
+                if self.seconds_to_wait().is_none() && !self.props.checking && !passphrase.as_str().is_empty() {
+                    self.props.submit_callback.emit(passphrase);
+                }
+                false
+            }
+            UnlockDialogMessage::Close => {
+                self.props.close_callback.emit(());
+                false
+            }
+            UnlockDialogMessage::Tick => {
+                if self.seconds_to_wait().is_none() {
+                    self.countdown = None;
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            if !props.show {
+                self.input.clear();
+                self.show_input = false;
+            }
+            self.props = props;
+            self.update_countdown();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        if !self.props.show {
+            return html! {};
+        }
+        let normalized = Passphrase::new(&self.input);
+        let wait = self.seconds_to_wait();
+        let feedback = match (wait, &self.props.error) {
+            (Some(seconds), _) => html! {
+                <div class="invalid-feedback d-block">{ format!("too many attempts, try again in {} seconds", seconds) }</div>
+            },
+            (None, Some(error)) => html! {
+                <div class="invalid-feedback d-block">{ error }</div>
+            },
+            (None, None) => html! {},
+        };
+        let input_class = if self.props.error.is_some() { "form-control is-invalid" } else { "form-control" };
+        html! {
+            <>
+                <div class="modal d-block" tabindex="-1" role="dialog" aria-labelledby="unlockDialogTitle">
+                    <div class="modal-dialog modal-dialog-centered" role="document">
+                        <form class="modal-content" onsubmit=self.link.callback(|e: FocusEvent| { e.prevent_default(); UnlockDialogMessage::Submit })>
+                            <div class="modal-header">
+                                <h5 class="modal-title" id="unlockDialogTitle">{"unlock"}</h5>
+                                <button type="button" class="close" aria-label="Close" onclick=self.link.callback(|_| UnlockDialogMessage::Close)>
+                                    <span aria-hidden="true">{"\u{d7}"}</span>
+                                </button>
+                            </div>
+                            <div class="modal-body">
+                                <label for="unlockPassphrase">{"本サイト所有者の氏名をひらがなで入力してください"}</label>
+                                <div class="input-group">
+                                    <input id="unlockPassphrase" class=input_class autocomplete="off" autofocus=true
+                                        type={ if self.show_input { "text" } else { "password" } }
+                                        value=self.input.clone()
+                                        oninput=self.link.callback(|e: InputData| UnlockDialogMessage::Input(e.value))/>
+                                    <div class="input-group-append">
+                                        <button type="button" class="btn btn-outline-secondary" onclick=self.link.callback(|_| UnlockDialogMessage::ToggleShowInput)>
+                                            { if self.show_input { "hide" } else { "show" } }
+                                        </button>
+                                    </div>
+                                    { feedback }
+                                </div>
+                                { if !self.input.is_empty() && normalized.as_str().is_empty() {
+                                    html! {
+                                        <small class="form-text text-warning">{ "read as nothing: only kana are read, enter the name in hiragana" }</small>
+                                    }
+                                } else if self.show_input && !self.input.is_empty() {
+                                    html! {
+                                        <small class="form-text text-muted">{ format!("read as: {}", normalized.as_str()) }</small>
+                                    }
+                                } else {
+                                    html! {}
+                                } }
+                            </div>
+                            <div class="modal-footer">
+                                <button type="button" class="btn btn-secondary" onclick=self.link.callback(|_| UnlockDialogMessage::Close)>{"cancel"}</button>
+                                <button type="submit" class="btn btn-primary" disabled={ wait.is_some() || self.props.checking || normalized.as_str().is_empty() }>
+                                    { if self.props.checking { "checking..." } else { "unlock" } }
+                                </button>
+                            </div>
+                        </form>
+                    </div>
+                </div>
+                <div class="modal-backdrop show"></div>
+            </>
+        }
+    }
+}
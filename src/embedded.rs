@@ -0,0 +1,21 @@
+//! Data files compiled into the binary with the `embedded-data` feature.
+//! The [store](crate::store) has them loaded from the start.
+//! `build.rs` parses each of them with its model type beforehand, so a malformed file fails the build.
+//! The works files are generated by `./generate_works.ps1` rather than committed, and are fetched as usual until they are.
+
+use crate::assets;
+
+/// Contents of the embedded file served at `addr`
+pub(crate) fn file(addr: &str) -> Option<&'static [u8]> {
+    match addr {
+        assets::LINK_DATA_JSON => Some(include_bytes!("../static/link.data.json")),
+        assets::QUALIFICATION_DATA_JSON => Some(include_bytes!("../static/qualification.data.json")),
+        assets::PROFILE_DATA_JSON => Some(include_bytes!("../static/profile.data.json")),
+        assets::LICENSE_DATA_JSON => Some(include_bytes!("../static/license.data.json")),
+        #[cfg(generated = "works.data.json")]
+        assets::WORKS_DATA_JSON => Some(include_bytes!("../static/works.data.json")),
+        #[cfg(generated = "works.graph.svg")]
+        assets::WORKS_GRAPH_SVG => Some(include_bytes!("../static/works.graph.svg")),
+        _ => None,
+    }
+}
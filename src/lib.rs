@@ -3,13 +3,22 @@
 #![allow(clippy::eval_order_dependence)]
 
 use wasm_bindgen::prelude::*;
-use yew::utils::document;
+use yew::utils::{document, window};
 
 use app::App;
 
 pub mod app;
+pub mod assets;
+pub mod cache;
 pub mod components;
+#[cfg(feature = "embedded-data")]
+mod embedded;
+pub mod protected;
+pub mod resource;
 pub mod routes;
+pub mod session;
+pub mod signed;
+pub mod store;
 
 // Use `wee_alloc` as the global allocator.
 #[global_allocator]
@@ -22,4 +31,17 @@ pub fn run() {
     log::debug!("Launch App!");
     let element = document().get_element_by_id("main").expect("failed get_element_by_id");
     yew::App::<App>::new().mount(element);
+    register_service_worker();
+}
+
+/// Offline support, `sw.js` is written next to `index.html` by `site_builder pwa` once parcel has built the site
+fn register_service_worker() {
+    let navigator = window().navigator();
+    // missing outside of secure contexts
+    if !js_sys::Reflect::has(&navigator, &JsValue::from_str("serviceWorker")).unwrap_or(false) {
+        return;
+    }
+    let failed = Closure::wrap(Box::new(|e: JsValue| log::warn!("service worker is not registered: {:?}", e)) as Box<dyn FnMut(JsValue)>);
+    let _ = navigator.service_worker().register("./sw.js").catch(&failed);
+    failed.forget();
 }
@@ -0,0 +1,249 @@
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+use portfolio_crypto::{KdfParams, KeySource, Keyring, OpenError, RecipientKeys};
+use portfolio_model::protected::{Protected, Reveal, RevealError};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use yew::prelude::*;
+
+use crate::assets;
+use crate::resource::Resource;
+use crate::session::UnlockKey;
+use crate::signed::{FetchError, parse};
+use crate::store;
+
+/// Data files with a keyring, with their signatures
+pub(crate) const PROTECTED_FILES: [(&str, &str); 3] = [
+    (assets::PROFILE_DATA_JSON, assets::PROFILE_DATA_JSON_SIG),
+    (assets::QUALIFICATION_DATA_JSON, assets::QUALIFICATION_DATA_JSON_SIG),
+    (assets::LINK_DATA_JSON, assets::LINK_DATA_JSON_SIG),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum DecryptError {
+    Open(OpenError),
+    NothingSealed,
+    /// no keyring could be loaded to check the key against
+    Unavailable,
+    InvalidData,
+    /// the passphrase is right, but it is for a later period
+    NotYetValid,
+    Expired,
+}
+
+impl Display for DecryptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecryptError::Open(OpenError::WrongKey) => write!(f, "wrong key"),
+            DecryptError::Open(OpenError::UnsupportedVersion(v)) => write!(f, "unsupported data version {}", v),
+            DecryptError::Open(OpenError::NotSealed) | DecryptError::Open(OpenError::Corrupted) => write!(f, "encrypted data is corrupted"),
+            DecryptError::NothingSealed => write!(f, "no encrypted values"),
+            DecryptError::Unavailable => write!(f, "failed to load the encrypted data"),
+            DecryptError::InvalidData => write!(f, "decrypted data is not valid"),
+            DecryptError::NotYetValid => write!(f, "this passphrase is not valid yet"),
+            DecryptError::Expired => write!(f, "this passphrase has expired"),
+        }
+    }
+}
+
+/// Data file fetched as [`Protected`], with the parts the unlock key could open revealed
+#[derive(Debug)]
+pub(crate) struct ProtectedResource<T> {
+    public: Resource<Protected<T>>,
+    revealed: Option<(String, T)>,
+    error: Option<DecryptError>,
+    addr: String,
+}
+
+impl<T> ProtectedResource<T> where T: Reveal + DeserializeOwned + Clone + 'static {
+    /// Take `addr` from the store, see [`Resource::fetch`].
+    /// `loaded` turns the parsed file or the error into a message of the component, hand it back with [`ProtectedResource::loaded`].
+    pub(crate) fn fetch<C: Component>(addr: &str, signature_addr: &str, link: &ComponentLink<C>, loaded: impl Fn(Result<Rc<Protected<T>>, FetchError>) -> C::Message + 'static) -> Self {
+        let public = Resource::fetch(addr, signature_addr, link.callback(loaded));
+        ProtectedResource { public, revealed: None, error: None, addr: addr.to_string() }
+    }
+
+    /// Store the fetch result and try `key` on the file, see [`ProtectedResource::unlock`].
+    pub(crate) fn loaded(&mut self, result: Result<Rc<Protected<T>>, FetchError>, key: Option<&UnlockKey>) {
+        self.public.loaded(result);
+        self.unlock(key);
+    }
+
+    pub(crate) fn retry(&mut self) {
+        self.revealed = None;
+        self.error = None;
+        self.public.retry();
+    }
+
+    /// Reveal what `key` opens, or go back to the public data for `None`.
+    /// Whether the key is right at all is told by [`check_key`], this file may just not be given to its recipient;
+    /// anything else that keeps the key from opening it is shown by [`ProtectedResource::error_view`].
+    pub(crate) fn unlock(&mut self, key: Option<&UnlockKey>) {
+        self.revealed = None;
+        self.error = None;
+        let (key, public) = match (key, self.public.data()) {
+            (Some(key), Some(public)) => (key, public),
+            _ => return,
+        };
+        match reveal(public, key) {
+            Ok((level, data)) => self.revealed = Some((level, data)),
+            // a file without a keyring has nothing to open
+            Err(DecryptError::NothingSealed) => {}
+            Err(DecryptError::Open(OpenError::WrongKey)) => log::info!("the key does not open {}", self.addr),
+            Err(e) => {
+                log::error!("error in unlocking {}: {}", self.addr, e);
+                self.error = Some(e);
+            }
+        }
+    }
+
+    /// Revealed data if the key opened anything, the public data otherwise
+    pub(crate) fn data(&self) -> Option<&T> {
+        self.revealed.as_ref().map(|(_, data)| data).or_else(|| self.public())
+    }
+
+    pub(crate) fn public(&self) -> Option<&T> {
+        self.public.data().map(|public| &public.data)
+    }
+
+    pub(crate) fn level(&self) -> Option<&str> {
+        self.revealed.as_ref().map(|(level, _)| level.as_str())
+    }
+
+    /// Alert for what kept the key from opening the file, nothing if it did or was not tried
+    pub(crate) fn error_view(&self, name: &str) -> Html {
+        match &self.error {
+            Some(e) => html! {
+                <div class="alert alert-danger mt-3" role="alert">
+                    { format!("failed to unlock {}: {}", name, e) }
+                </div>
+            },
+            None => html! {},
+        }
+    }
+
+    /// See [`Resource::status_view`]
+    pub(crate) fn status_view(&self, retry: &Callback<()>) -> Html {
+        self.public.status_view(retry)
+    }
+}
+
+fn reveal<T: Reveal + Clone>(public: &Protected<T>, key: &UnlockKey) -> Result<(String, T), DecryptError> {
+    let keyring = public.keyring.as_deref().ok_or(DecryptError::NothingSealed)?;
+    let keys = Keyring::decode(keyring)
+        .and_then(|keyring| keyring.recipient_keys(key))
+        .map_err(DecryptError::Open)?;
+    check_validity(&keys)?;
+    let mut data = public.data.clone();
+    data.reveal(&mut |sealed, path| keys.get(sealed.key).map(|key| portfolio_crypto::open_field(&sealed.data, path, key)).transpose())
+        .map_err(|e| match e {
+            RevealError::Open(e) => DecryptError::Open(e),
+            RevealError::Invalid(_) => DecryptError::InvalidData,
+        })?;
+    Ok((keys.label, data))
+}
+
+/// The keyring of the protected file `addr` in the store, `None` while it is loading
+/// and `Some(Err)` if the file cannot be had.
+fn stored_keyring(addr: &str) -> Option<Result<Option<String>, ()>> {
+    Some(match store::bytes(addr)? {
+        Ok(data) => parse::<Protected<IgnoredAny>>(addr, &data).map(|public| public.keyring).map_err(drop),
+        Err(_) => Err(()),
+    })
+}
+
+/// The distinct [`KdfParams`] of the recipient slots of the [`PROTECTED_FILES`] loaded so far, in the order they are tried
+pub(crate) fn recipient_params() -> Vec<KdfParams> {
+    let mut params: Vec<KdfParams> = Vec::new();
+    let keyrings = PROTECTED_FILES.iter()
+        .filter_map(|&(addr, _)| match stored_keyring(addr) {
+            Some(Ok(Some(keyring))) => Keyring::decode(&keyring).ok(),
+            _ => None,
+        });
+    for keyring in keyrings {
+        for recipient in keyring.recipients() {
+            if !params.contains(&recipient.params) {
+                params.push(recipient.params.clone());
+            }
+        }
+    }
+    params
+}
+
+/// Try `key` on the keyring of each of the [`PROTECTED_FILES`] in the store, whichever page is shown.
+/// `Some(Ok)` with the recipient label once one of them opens, `Some(Err)` once all of them are loaded and none does,
+/// `None` while that cannot be told yet; subscribe to the files to be told when it can.
+pub(crate) fn check_key<K: KeySource>(key: &K) -> Option<Result<String, DecryptError>> {
+    let mut loading = false;
+    let mut unavailable = false;
+    let mut error: Option<DecryptError> = None;
+    for &(addr, _) in PROTECTED_FILES.iter() {
+        let keyring = match stored_keyring(addr) {
+            None => {
+                loading = true;
+                continue;
+            }
+            Some(Ok(Some(keyring))) => keyring,
+            Some(Ok(None)) => continue,
+            Some(Err(())) => {
+                unavailable = true;
+                continue;
+            }
+        };
+        let result = Keyring::decode(&keyring)
+            .and_then(|keyring| keyring.recipient_keys(key))
+            .map_err(DecryptError::Open)
+            .and_then(|keys| {
+                check_validity(&keys)?;
+                Ok(keys.label)
+            });
+        match result {
+            Ok(label) => return Some(Ok(label)),
+            // the most telling error of all the files
+            Err(e) => {
+                if error.iter().all(|error| rank(&e) > rank(error)) {
+                    error = Some(e);
+                }
+            }
+        }
+    }
+    if loading {
+        return None;
+    }
+    Some(Err(error.unwrap_or(if unavailable { DecryptError::Unavailable } else { DecryptError::NothingSealed })))
+}
+
+/// How much an error of [`check_key`] says about the key, a broken file least
+fn rank(error: &DecryptError) -> u8 {
+    match error {
+        DecryptError::NotYetValid | DecryptError::Expired => 2,
+        DecryptError::Open(OpenError::WrongKey) => 1,
+        _ => 0,
+    }
+}
+
+/// Refuse keys outside the validity window of their recipient slot
+pub(crate) fn check_validity(keys: &RecipientKeys) -> Result<(), DecryptError> {
+    let now = (js_sys::Date::now() / 1000.0) as u64;
+    match keys.validity.not_before {
+        Some(not_before) if now < not_before => Err(DecryptError::NotYetValid),
+        _ if !keys.validity.contains(now) => Err(DecryptError::Expired),
+        _ => Ok(()),
+    }
+}
+
+/// Line standing in for `count` list entries that are still sealed
+pub(crate) fn locked_entries(count: usize, request_unlock: &Callback<()>) -> Html {
+    if count == 0 {
+        return html! {};
+    }
+    html! {
+        <div class="secret-value h5 mt-3">
+            <span class="mr-2" aria-hidden="true">{"\u{1f512}"}</span>
+            { format!("{} private {}", count, if count == 1 { "entry" } else { "entries" }) }
+            <button type="button" class="btn btn-link btn-sm align-baseline ml-2 p-0" onclick=request_unlock.reform(|_| ())>
+                { "unlock to view" }
+            </button>
+        </div>
+    }
+}
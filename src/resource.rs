@@ -0,0 +1,89 @@
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use yew::prelude::*;
+
+use crate::signed::FetchError;
+use crate::store::{self, Subscription};
+
+#[derive(Debug)]
+enum ResourceState<T> {
+    Loading,
+    Loaded(Rc<T>),
+    Failed(FetchError),
+}
+
+/// Data file from the [store](crate::store), checked with its signature when fetched
+#[derive(Debug)]
+pub(crate) struct Resource<T> {
+    addr: String,
+    state: ResourceState<T>,
+    _subscription: Subscription,
+}
+
+impl<T: DeserializeOwned + 'static> Resource<T> {
+    /// Take `addr` from the store, which fetches it unless another page did already.
+    /// A file that is there is loaded at once, otherwise `callback` gets the result, hand it back with [`Resource::loaded`].
+    pub(crate) fn fetch(addr: &str, signature_addr: &str, callback: Callback<Result<Rc<T>, FetchError>>) -> Self {
+        let key = addr.to_string();
+        let subscription = store::subscribe(addr, Some(signature_addr), Callback::from(move |()| {
+            if let Some(result) = store::get::<T>(&key) {
+                callback.emit(result);
+            }
+        }));
+        let state = match store::get::<T>(addr) {
+            None => ResourceState::Loading,
+            Some(Ok(data)) => ResourceState::Loaded(data),
+            Some(Err(e)) => ResourceState::Failed(e),
+        };
+        Resource { addr: addr.to_string(), state, _subscription: subscription }
+    }
+
+    pub(crate) fn retry(&mut self) {
+        self.state = ResourceState::Loading;
+        store::retry(&self.addr);
+    }
+
+    pub(crate) fn loaded(&mut self, result: Result<Rc<T>, FetchError>) {
+        self.state = match result {
+            Ok(data) => ResourceState::Loaded(data),
+            Err(e) => ResourceState::Failed(e),
+        };
+    }
+
+    pub(crate) fn data(&self) -> Option<&T> {
+        match &self.state {
+            ResourceState::Loaded(data) => Some(data.as_ref()),
+            ResourceState::Loading | ResourceState::Failed(_) => None,
+        }
+    }
+
+    /// Spinner while loading and the error with a retry button on failure, nothing once loaded
+    pub(crate) fn status_view(&self, retry: &Callback<()>) -> Html {
+        match &self.state {
+            ResourceState::Loading => html! {
+                <div class="d-flex justify-content-center m-5">
+                    <div class="spinner-border text-secondary" role="status">
+                        <span class="sr-only">{"loading..."}</span>
+                    </div>
+                </div>
+            },
+            ResourceState::Loaded(_) => html! {},
+            ResourceState::Failed(error) => {
+                let (class, message) = match error {
+                    FetchError::BadSignature => ("alert alert-warning m-2", "This content could not be verified and may have been altered on its way to you, so it is not shown."),
+                    FetchError::Failed => ("alert alert-danger m-2", "Failed to load this content."),
+                    FetchError::Invalid => ("alert alert-danger m-2", "This content is broken."),
+                };
+                html! {
+                    <div class=class role="alert">
+                        { message }
+                        <button type="button" class="btn btn-link btn-sm align-baseline ml-2 p-0" onclick=retry.reform(|_| ())>
+                            { "retry" }
+                        </button>
+                    </div>
+                }
+            }
+        }
+    }
+}
@@ -1,20 +1,20 @@
 use std::ops::Deref;
+use std::rc::Rc;
 
-use anyhow::Error;
-use once_cell::sync::OnceCell;
-use serde::Deserialize;
-use yew::format::Json;
+use portfolio_model::license::LicenseDocument;
 use yew::prelude::*;
-use yew::services::fetch::*;
 use yewtil::NeqAssign;
 
-use crate::routes::request;
+use crate::assets;
+use crate::resource::Resource;
+use crate::signed::FetchError;
 
 #[derive(Debug)]
 pub(crate) struct License {
     props: LicenseProperties,
     link: ComponentLink<Self>,
-    task: Option<FetchTask>,
+    /// taken from the store the first time the page is shown
+    data: Option<Resource<LicenseDocument>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Properties)]
@@ -24,28 +24,16 @@ pub(crate) struct LicenseProperties {
 
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum LicenseMessage {
-    FetchLicenseData(Vec<LicenseData>),
-    None,
+    FetchLicenseData(Result<Rc<LicenseDocument>, FetchError>),
+    Retry,
 }
 
-#[derive(Debug, PartialEq, Clone, Deserialize)]
-pub(crate) struct LicenseData {
-    name: String,
-    version: Option<String>,
-    authors: Option<String>,
-    repository: Option<String>,
-    license: Option<String>,
-    description: Option<String>,
-}
-
-static CELL: OnceCell<Vec<LicenseData>> = OnceCell::new();
-
 impl Component for License {
     type Message = LicenseMessage;
     type Properties = LicenseProperties;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let mut component = Self { props, link, task: None };
+        let mut component = Self { props, link, data: None };
         component.init();
         component
     }
@@ -54,9 +42,17 @@ impl Component for License {
         match msg {
             LicenseMessage::FetchLicenseData(data) => {
                 log::info!("{:?}", data);
-                CELL.set(data).is_ok()
+                if let Some(resource) = &mut self.data {
+                    resource.loaded(data);
+                }
+                true
+            }
+            LicenseMessage::Retry => {
+                if let Some(resource) = &mut self.data {
+                    resource.retry();
+                }
+                true
             }
-            LicenseMessage::None => false,
         }
     }
 
@@ -70,8 +66,7 @@ impl Component for License {
     }
 
     fn view(&self) -> Html {
-        let default = Vec::new();
-        let link_table = CELL.get().unwrap_or(&default)
+        let link_table = self.data.as_ref().and_then(Resource::data).map(|document| document.data.as_slice()).unwrap_or_default()
             .iter()
             .map(|data| {
                 html! {
@@ -93,6 +88,10 @@ impl Component for License {
         html! {
             <>
                 <h1 class="m-2" style={style}>{"License: Cargo crates"}</h1>
+                { match &self.data {
+                    Some(data) if self.props.show => data.status_view(&self.link.callback(|_| LicenseMessage::Retry)),
+                    _ => html! {},
+                } }
                 <div class="card-columns" style={style}>
                     {for link_table}
                 </div>
@@ -103,27 +102,9 @@ impl Component for License {
 
 impl License {
     fn init(&mut self) {
-        self.task = if CELL.get().is_none() && self.props.show {
-            let callback = self.link.callback(|response: Response<Json<Result<Vec<LicenseData>, Error>>>| {
-                if response.status().is_success() {
-                    match response.into_body() {
-                        Json(Ok(s)) => {
-                            LicenseMessage::FetchLicenseData(s)
-                        }
-                        Json(Err(e)) => {
-                            log::error!("error in fetching link.data.json: {:?}", e);
-                            LicenseMessage::None
-                        }
-                    }
-                } else {
-                    log::error!("error in fetching link.data.json code: {}", response.status());
-                    LicenseMessage::None
-                }
-            });
-            let task = request("/license.data.61b04f4a.json", callback);
-            Some(task)
-        } else {
-            None
-        };
+        if self.data.is_none() && self.props.show {
+            let callback = self.link.callback(LicenseMessage::FetchLicenseData);
+            self.data = Some(Resource::fetch(assets::LICENSE_DATA_JSON, assets::LICENSE_DATA_JSON_SIG, callback));
+        }
     }
 }
@@ -1,91 +1,76 @@
 use std::ops::Deref;
+use std::rc::Rc;
 
-use anyhow::Error;
-use serde::Deserialize;
-use yew::format::{Json, Nothing};
+use portfolio_model::link::{LinkData, LinkDocument, LinkKeyData, LinkValueData};
+use portfolio_model::protected::MaybeSealed;
 use yew::prelude::*;
-use yew::services::fetch::*;
-use yew::services::FetchService;
+
+use crate::assets;
+use crate::protected::{ProtectedResource, locked_entries};
+use crate::session::UnlockKey;
+use crate::signed::FetchError;
 
 pub(crate) struct Links {
+    props: LinkProperties,
     link: ComponentLink<Self>,
-    task: FetchTask,
-    link_data: Vec<LinkData>,
+    link_data: ProtectedResource<Vec<MaybeSealed<LinkData>>>,
 }
 
 pub(crate) enum LinkMessage {
-    FetchLinkData(Vec<LinkData>),
-    None,
-}
-
-#[derive(Debug, PartialEq, Deserialize)]
-#[serde(untagged)]
-enum LinkValueData {
-    DisplayOnly(String),
-    WithLink { display: String, link: String },
+    FetchLinkData(Result<Rc<LinkDocument>, FetchError>),
+    Retry,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-#[serde(untagged)]
-enum LinkKeyData {
-    NameOnly(String),
-    WithImage { name: String, image: String },
-}
-
-#[derive(Debug, PartialEq, Deserialize)]
-pub(crate) struct LinkData {
-    key: LinkKeyData,
-    value: LinkValueData,
+#[derive(Clone, Debug, Default, PartialEq, Properties)]
+pub(crate) struct LinkProperties {
+    #[prop_or_default]
+    pub(crate) encrypt_key: Option<UnlockKey>,
+    #[prop_or_default]
+    pub(crate) request_unlock: Callback<()>,
 }
 
 impl Component for Links {
     type Message = LinkMessage;
-    type Properties = ();
+    type Properties = LinkProperties;
 
-    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let request = Request::get("/link.data.67860567.json").body(Nothing).unwrap();
-        let callback = link.callback(|response: Response<Json<Result<Vec<LinkData>, Error>>>| {
-            if response.status().is_success() {
-                match response.into_body() {
-                    Json(Ok(s)) => {
-                        LinkMessage::FetchLinkData(s)
-                    }
-                    Json(Err(e)) => {
-                        log::error!("error in fetching link.data.json: {:?}", e);
-                        LinkMessage::None
-                    }
-                }
-            } else {
-                log::error!("error in fetching link.data.json code: {}", response.status());
-                LinkMessage::None
-            }
-        });
-        let task = FetchService::fetch(request, callback).unwrap();
-        Self { link, task, link_data: Vec::new() }
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut link_data = ProtectedResource::fetch(assets::LINK_DATA_JSON, assets::LINK_DATA_JSON_SIG, &link, LinkMessage::FetchLinkData);
+        link_data.unlock(props.encrypt_key.as_ref());
+        Self { props, link, link_data }
     }
 
     fn update(&mut self, msg: Self::Message) -> bool {
         match msg {
             LinkMessage::FetchLinkData(data) => {
                 log::info!("{:?}", data);
-                if self.link_data != data {
-                    self.link_data = data;
-                    true
-                } else {
-                    false
-                }
+                self.link_data.loaded(data, self.props.encrypt_key.as_ref());
+                true
+            }
+            LinkMessage::Retry => {
+                self.link_data.retry();
+                true
             }
-            LinkMessage::None => false,
         }
     }
 
-    fn change(&mut self, _props: Self::Properties) -> bool {
-        false
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            if self.props.encrypt_key != props.encrypt_key {
+                self.link_data.unlock(props.encrypt_key.as_ref());
+            }
+            self.props = props;
+            true
+        } else {
+            false
+        }
     }
 
     fn view(&self) -> Html {
-        let link_table = self.link_data
+        let link_data = self.link_data.data().map(Vec::as_slice).unwrap_or_default();
+        let locked = link_data.iter().filter(|data| data.public().is_none()).count();
+        let link_table = link_data
             .iter()
+            .filter_map(MaybeSealed::public)
             .map(|data| {
                 let key = match &data.key {
                     LinkKeyData::NameOnly(name) => html! {
@@ -113,7 +98,10 @@ impl Component for Links {
         html! {
             <>
                 <h1 class="m-2">{"Links"}</h1>
+                { self.link_data.status_view(&self.link.callback(|_| LinkMessage::Retry)) }
+                { self.link_data.error_view("links") }
                 {for link_table}
+                { locked_entries(locked, &self.props.request_unlock) }
             </>
         }
     }
@@ -3,6 +3,12 @@ use yew::prelude::*;
 use yew::services::fetch::*;
 use yew::services::FetchService;
 use yew_router::prelude::*;
+use yew_router::route::Route;
+use yew_router::switch::AllowMissing;
+
+use crate::assets;
+use crate::cache::Validators;
+use crate::routes::works::WorksSelection;
 
 pub(crate) mod home;
 pub(crate) mod profile;
@@ -10,20 +16,32 @@ pub(crate) mod qualifications;
 pub(crate) mod links;
 pub(crate) mod works;
 pub(crate) mod license;
+pub(crate) mod not_found;
 
-/// App routes
+/// App routes, in the fragment such as `/#profile`, or with the `path-routing` feature in the path such as `/profile`.
+/// Paths need a page for each of them on a static host, which `site_builder pages` writes.
 #[derive(Switch, Debug, Clone, PartialEq)]
 pub enum AppRoute {
-    #[to = "/#profile"]
+    #[cfg_attr(not(feature = "path-routing"), to = "/#profile")]
+    #[cfg_attr(feature = "path-routing", to = "/profile")]
     Profile,
-    #[to = "/#qualification"]
+    #[cfg_attr(not(feature = "path-routing"), to = "/#qualification")]
+    #[cfg_attr(feature = "path-routing", to = "/qualification")]
     Qualifications,
-    #[to = "/#link"]
+    #[cfg_attr(not(feature = "path-routing"), to = "/#link")]
+    #[cfg_attr(feature = "path-routing", to = "/link")]
     Links,
-    #[to = "/#works"]
-    Works,
-    #[to = "/#license"]
+    /// with the node of the graph to show, `works/repository/Portfolio`
+    #[cfg_attr(not(feature = "path-routing"), to = "/#works{*:selection}")]
+    #[cfg_attr(feature = "path-routing", to = "/works{*:selection}")]
+    Works(AllowMissing<WorksSelection>),
+    #[cfg_attr(not(feature = "path-routing"), to = "/#license")]
+    #[cfg_attr(feature = "path-routing", to = "/license")]
     License,
+    /// anything else, see also the redirect of the `Router` in `App`
+    #[cfg_attr(not(feature = "path-routing"), to = "/#{*:path}")]
+    #[cfg_attr(feature = "path-routing", to = "/{*:path}")]
+    NotFound(String),
     #[to = "/"]
     Home,
 }
@@ -34,14 +52,71 @@ impl ToString for AppRoute {
             AppRoute::Profile => "Profile",
             AppRoute::Qualifications => "Qualifications",
             AppRoute::Links => "Links",
-            AppRoute::Works => "Works",
+            AppRoute::Works(_) => "Works",
             AppRoute::License => "License",
+            AppRoute::NotFound(_) => "Not Found",
             AppRoute::Home => "Home",
         }.to_string()
     }
 }
 
-fn request<R: 'static + From<std::result::Result<Vec<u8>, anyhow::Error>>>(addr: &str, callback: Callback<Response<R>>) -> FetchTask {
-    let request = Request::get(addr).body(Nothing).unwrap();
-    FetchService::fetch_binary(request, callback).unwrap()
+impl AppRoute {
+    /// The page the route shows, without what is selected on it
+    pub(crate) fn page(&self) -> AppRoute {
+        match self {
+            AppRoute::Works(_) => AppRoute::Works(AllowMissing(None)),
+            route => route.clone(),
+        }
+    }
+
+    /// The route as it is written after the root of the site, `works/repository/Portfolio`
+    pub(crate) fn path(&self) -> String {
+        relative(&Route::<()>::from(self.clone()).route).to_string()
+    }
+
+    /// The page telling that `route` matches none of the others
+    pub(crate) fn not_found(route: &str) -> AppRoute {
+        AppRoute::NotFound(relative(route).to_string())
+    }
+
+    /// Files the page shows with their signatures, fetched in advance when a link to it is hovered
+    pub(crate) fn data_files(&self) -> &'static [(&'static str, Option<&'static str>)] {
+        match self {
+            AppRoute::Profile => &[(assets::PROFILE_DATA_JSON, Some(assets::PROFILE_DATA_JSON_SIG))],
+            AppRoute::Qualifications => &[(assets::QUALIFICATION_DATA_JSON, Some(assets::QUALIFICATION_DATA_JSON_SIG))],
+            AppRoute::Links => &[(assets::LINK_DATA_JSON, Some(assets::LINK_DATA_JSON_SIG))],
+            AppRoute::Works(_) => &[(assets::WORKS_DATA_JSON, Some(assets::WORKS_DATA_JSON_SIG)), (assets::WORKS_GRAPH_SVG, None)],
+            AppRoute::License => &[(assets::LICENSE_DATA_JSON, Some(assets::LICENSE_DATA_JSON_SIG))],
+            AppRoute::NotFound(_) | AppRoute::Home => &[],
+        }
+    }
+}
+
+/// `/#works` or `/works` -> `works`
+fn relative(route: &str) -> &str {
+    route.trim_start_matches('/').trim_start_matches('#')
+}
+
+/// Percent-encode `segment` to be a part of a route
+pub(crate) fn encode_segment(segment: &str) -> String {
+    String::from(js_sys::encode_uri_component(segment))
+}
+
+/// A part of a route as it was meant, left as it is if it is not validly encoded
+pub(crate) fn decode_segment(segment: &str) -> String {
+    js_sys::decode_uri_component(segment).map(String::from).unwrap_or_else(|_| segment.to_string())
+}
+
+/// GET `addr`, with the validators of a copy at hand the server answers 304 Not Modified if the copy is current
+pub(crate) fn request<R: 'static + From<std::result::Result<Vec<u8>, anyhow::Error>>>(addr: &str, validators: Option<&Validators>, callback: Callback<Response<R>>) -> FetchTask {
+    let mut request = Request::get(addr);
+    if let Some(validators) = validators {
+        if let Some(etag) = &validators.etag {
+            request = request.header("If-None-Match", etag.as_str());
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header("If-Modified-Since", last_modified.as_str());
+        }
+    }
+    FetchService::fetch_binary(request.body(Nothing).unwrap(), callback).unwrap()
 }
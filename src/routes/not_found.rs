@@ -0,0 +1,186 @@
+use std::rc::Rc;
+
+use portfolio_model::works::WorksData;
+use yew::prelude::*;
+use yew_router::prelude::*;
+use yew_router::switch::AllowMissing;
+use yewtil::NeqAssign;
+
+use crate::assets;
+use crate::resource::Resource;
+use crate::routes::{AppRoute, decode_segment};
+use crate::routes::works::SelectedNode;
+use crate::signed::FetchError;
+
+/// Pages offered when the first segment of an unknown path is close to theirs
+const PAGES: [AppRoute; 5] = [AppRoute::Profile, AppRoute::Works(AllowMissing(None)), AppRoute::Qualifications, AppRoute::Links, AppRoute::License];
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Page for a route that does not exist, with the known ones close to it
+#[derive(Debug)]
+pub(crate) struct NotFound {
+    props: NotFoundProperties,
+    link: ComponentLink<Self>,
+    /// searched for a node of the graph when the path is a link into it
+    works_data: Option<Resource<WorksData>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub(crate) struct NotFoundProperties {
+    /// as it is written after the root of the site, see [`AppRoute::path`]
+    pub(crate) path: String,
+}
+
+pub(crate) enum NotFoundMessage {
+    FetchWorksData(Result<Rc<WorksData>, FetchError>),
+    Retry,
+}
+
+impl Component for NotFound {
+    type Message = NotFoundMessage;
+    type Properties = NotFoundProperties;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut component = Self { props, link, works_data: None };
+        component.init();
+        component
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            NotFoundMessage::FetchWorksData(data) => {
+                if let Some(resource) = &mut self.works_data {
+                    resource.loaded(data);
+                }
+                true
+            }
+            NotFoundMessage::Retry => {
+                if let Some(resource) = &mut self.works_data {
+                    resource.retry();
+                }
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props.neq_assign(props) {
+            self.init();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let suggestions = self.suggestions();
+        html! {
+            <>
+                <h1 class="m-2">{ "Not Found" }</h1>
+                <p class="m-2">
+                    { "There is no page at " }<code>{ decode_segment(&self.props.path) }</code>{ "." }
+                </p>
+                { self.works_data.as_ref().map(|resource| resource.status_view(&self.link.callback(|_| NotFoundMessage::Retry))).unwrap_or_default() }
+                {
+                    if suggestions.is_empty() {
+                        html! {}
+                    } else {
+                        html! {
+                            <div class="m-2">
+                                { "Did you mean:" }
+                                <ul>
+                                    { for suggestions.into_iter().map(|(route, label)| html! {
+                                        <li><RouterAnchor<AppRoute> route=route>{ label }</RouterAnchor<AppRoute>></li>
+                                    }) }
+                                </ul>
+                            </div>
+                        }
+                    }
+                }
+                <p class="m-2">
+                    <RouterAnchor<AppRoute> route=AppRoute::Home>{ "Back to the top page" }</RouterAnchor<AppRoute>>
+                </p>
+            </>
+        }
+    }
+}
+
+impl NotFound {
+    fn init(&mut self) {
+        if self.works_data.is_none() && self.works_name().is_some() {
+            self.works_data = Some(Resource::fetch(assets::WORKS_DATA_JSON, assets::WORKS_DATA_JSON_SIG, self.link.callback(NotFoundMessage::FetchWorksData)));
+        }
+    }
+
+    /// The last segment of a path like `works/repository/<name>`, whose node may have been renamed
+    fn works_name(&self) -> Option<String> {
+        let mut segments = self.props.path.split('/').filter(|segment| !segment.is_empty());
+        let page = segments.next()?;
+        let name = segments.last()?;
+        closeness(page, &AppRoute::Works(AllowMissing(None)).path())?;
+        Some(decode_segment(name))
+    }
+
+    /// Routes close to the requested path with their labels, the closest first
+    fn suggestions(&self) -> Vec<(AppRoute, String)> {
+        let mut suggestions = Vec::new();
+        let page = self.props.path.split('/').find(|segment| !segment.is_empty()).unwrap_or_default();
+        for route in PAGES.iter() {
+            let name = route.to_string();
+            let closest = closeness(page, &route.path()).into_iter().chain(closeness(page, &name)).min();
+            if let Some(closeness) = closest {
+                suggestions.push((closeness, route.clone(), name));
+            }
+        }
+        if let (Some(name), Some(works)) = (self.works_name(), self.works_data.as_ref().and_then(Resource::data)) {
+            for node in SelectedNode::all(works) {
+                let (node_name, selection) = match (node.name(works), node.selection(works)) {
+                    (Some(node_name), Some(selection)) => (node_name, selection),
+                    _ => continue,
+                };
+                if let Some(closeness) = closeness(&name, node_name) {
+                    let label = format!("{} ({})", node_name, selection.kind());
+                    suggestions.push((closeness, AppRoute::Works(AllowMissing(Some(selection))), label));
+                }
+            }
+        }
+        suggestions.sort_by_key(|(closeness, _, _)| *closeness);
+        suggestions.into_iter().take(MAX_SUGGESTIONS).map(|(_, route, label)| (route, label)).collect()
+    }
+}
+
+/// How far `requested` is from `known`, ignoring case, or `None` if it is too far to be a typo of it
+fn closeness(requested: &str, known: &str) -> Option<usize> {
+    let requested = requested.to_lowercase();
+    let known = known.to_lowercase();
+    if requested.is_empty() || known.is_empty() {
+        return None;
+    }
+    // a part of the other, unless it is so short that it is a part of anything
+    let shorter = requested.chars().count().min(known.chars().count());
+    if shorter >= 3 && (known.contains(&requested) || requested.contains(&known)) {
+        return Some(0);
+    }
+    let distance = edit_distance(&requested, &known);
+    if distance <= (known.chars().count() / 3).max(1) {
+        Some(distance)
+    } else {
+        None
+    }
+}
+
+/// Levenshtein distance in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(diagonal + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
@@ -1,72 +1,33 @@
-use anyhow::Error;
-use serde::Deserialize;
-use yew::format::Json;
+use std::rc::Rc;
+
+use portfolio_model::profile::{ProfileData, ProfileDocument, RubyString, WrappedString};
 use yew::prelude::*;
-use yew::services::fetch::*;
 
-use crate::routes::request;
-
-#[derive(Clone, Debug)]
-enum ProfileDataList {
-    Raw(Vec<ProfileData>),
-    Encrypted(Vec<ProfileData>),
-}
-
-impl ProfileDataList {
-    fn data(&self) -> &Vec<ProfileData> {
-        match self {
-            ProfileDataList::Raw(d) | ProfileDataList::Encrypted(d) => d
-        }
-    }
-}
+use crate::assets;
+use crate::components::sealed_asset::SealedAssetView;
+use crate::protected::ProtectedResource;
+use crate::session::UnlockKey;
+use crate::signed::FetchError;
 
 #[derive(Debug)]
 pub(crate) struct Profile {
     props: ProfileProperties,
     link: ComponentLink<Self>,
-    profile_data: ProfileDataList,
-    profile_data_enc: Vec<u8>,
-    task: (FetchTask, FetchTask),
+    profile_data: ProtectedResource<Vec<ProfileData>>,
 }
 
 #[derive(Clone, Debug)]
 pub(crate) enum ProfileMessage {
-    FetchProfileData(Vec<ProfileData>),
-    FetchProfileDataEnc(Vec<u8>),
-    None,
+    FetchProfileData(Result<Rc<ProfileDocument>, FetchError>),
+    Retry,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Properties)]
 pub(crate) struct ProfileProperties {
     #[prop_or_default]
-    pub(crate) encrypt_key: Option<aes::Key>
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-struct RubyString {
-    value: String,
-    ruby: String,
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-#[serde(untagged)]
-enum WrappedString {
-    None,
-    Normal(String),
-    WithRuby(Vec<RubyString>),
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-struct ProfileValueData {
-    key: String,
-    value: WrappedString,
-    status: Option<String>,
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-pub(crate) struct ProfileData {
-    category_name: String,
-    values: Vec<ProfileValueData>,
+    pub(crate) encrypt_key: Option<UnlockKey>,
+    #[prop_or_default]
+    pub(crate) request_unlock: Callback<()>,
 }
 
 impl Component for Profile {
@@ -74,64 +35,33 @@ impl Component for Profile {
     type Properties = ProfileProperties;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let callback = link.callback(|response: Response<Json<Result<Vec<ProfileData>, Error>>>| {
-            if response.status().is_success() {
-                match response.into_body() {
-                    Json(Ok(s)) => {
-                        ProfileMessage::FetchProfileData(s)
-                    }
-                    Json(Err(e)) => {
-                        log::error!("error in fetching profile.data.json: {:?}", e);
-                        ProfileMessage::None
-                    }
-                }
-            } else {
-                log::error!("error in fetching profile.data.json code: {}", response.status());
-                ProfileMessage::None
-            }
-        });
-        let task_json = request("/profile.data.a36e7575.json", callback);
-        let callback = link.callback(|response: Response<Result<Vec<u8>, Error>>| {
-            if response.status().is_success() {
-                match response.into_body() {
-                    Ok(data) => ProfileMessage::FetchProfileDataEnc(data),
-                    Err(e) => {
-                        log::error!("error in fetching profile.data.enc.bin: {:?}", e);
-                        ProfileMessage::None
-                    }
-                }
-            } else {
-                log::error!("error in fetching profile.data.enc.bin code: {}", response.status());
-                ProfileMessage::None
-            }
-        });
-        let task_enc = request("/profile.data.enc.4cdf9278.bin", callback);
-        Self { props, link, profile_data: ProfileDataList::Raw(Vec::new()), profile_data_enc: Vec::new(), task: (task_json, task_enc) }
+        let profile_data = ProtectedResource::fetch(assets::PROFILE_DATA_JSON, assets::PROFILE_DATA_JSON_SIG, &link, ProfileMessage::FetchProfileData);
+        let mut profile = Self { props, link, profile_data };
+        // the file may be in the store already
+        profile.profile_data.unlock(profile.props.encrypt_key.as_ref());
+        profile
     }
 
     fn update(&mut self, msg: Self::Message) -> bool {
         match msg {
-            ProfileMessage::FetchProfileData(s) => {
-                match &mut self.profile_data {
-                    ProfileDataList::Raw(d) => {
-                        *d = s;
-                        true
-                    }
-                    ProfileDataList::Encrypted(_) => false
-                }
+            ProfileMessage::FetchProfileData(data) => {
+                self.profile_data.loaded(data, self.props.encrypt_key.as_ref());
+                true
             }
-            ProfileMessage::FetchProfileDataEnc(s) => {
-                self.profile_data_enc = s;
-                self.try_decrypt()
+            ProfileMessage::Retry => {
+                self.profile_data.retry();
+                true
             }
-            ProfileMessage::None => false
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> bool {
         if self.props != props {
+            let key_changed = self.props.encrypt_key != props.encrypt_key;
             self.props = props;
-            self.try_decrypt();
+            if key_changed {
+                self.profile_data.unlock(self.props.encrypt_key.as_ref());
+            }
             true
         } else {
             false
@@ -139,19 +69,19 @@ impl Component for Profile {
     }
 
     fn view(&self) -> Html {
-        let data = self.profile_data.data().iter()
-            .map(|data| {
-                let values = data.values.iter()
-                    .map(|data| {
-                        let value: Html = match &data.value {
-                            WrappedString::None => html! {},
-                            WrappedString::Normal(s) => html! {{s}},
-                            WrappedString::WithRuby(s) => html! {
-                                { for s.iter()
-                                    .map(|RubyString { value, ruby }| html! {
-                                        <ruby>{value}<rt>{ruby}</rt></ruby>
-                                    }) }
+        let public = self.profile_data.public().map(Vec::as_slice).unwrap_or_default();
+        let shown = self.profile_data.data().map(Vec::as_slice).unwrap_or_default();
+        let data = public.iter().zip(shown)
+            .map(|(public, data)| {
+                let values = public.values.iter().zip(&data.values)
+                    .map(|(public, data)| {
+                        let value = self.value_html(&data.value);
+                        let value = if public.sealed.is_some() && data.sealed.is_none() {
+                            html! {
+                                <span class="revealed-value" title="revealed by unlock">{ value }</span>
                             }
+                        } else {
+                            value
                         };
                         if let Some(status) = &data.status {
                             html! {
@@ -190,8 +120,17 @@ impl Component for Profile {
                 }
             });
 
+        let error = self.profile_data.error_view("profile");
+
+        let level = self.profile_data.level().map(|level| html! {
+            <div class="text-muted mt-3">{ format!("unlocked as \"{}\"", level) }</div>
+        }).unwrap_or_default();
+
         html! {
             <>
+                { self.profile_data.status_view(&self.link.callback(|_| ProfileMessage::Retry)) }
+                { error }
+                { level }
                 { for data }
             </>
         }
@@ -199,31 +138,28 @@ impl Component for Profile {
 }
 
 impl Profile {
-    fn try_decrypt(&mut self) -> bool {
-        if let Some(key) = &self.props.encrypt_key {
-            let mut decrypted = aes::decrypt(&self.profile_data_enc, key);
-            while decrypted.last() == Some(&0) { decrypted.pop(); }
-            match Json::<Result<Vec<ProfileData>, Error>>::from(Ok(decrypted)) {
-                Json(Ok(data)) => {
-                    match &mut self.profile_data {
-                        ProfileDataList::Raw(_) => {
-                            self.profile_data = ProfileDataList::Encrypted(data);
-                            true
-                        }
-                        ProfileDataList::Encrypted(d) => {
-                            if d == &data {
-                                false
-                            } else {
-                                *d = data;
-                                true
-                            }
-                        }
-                    }
-                }
-                Json(Err(_)) => false
-            }
-        } else {
-            false
+    fn value_html(&self, value: &WrappedString) -> Html {
+        match value {
+            WrappedString::None => html! {},
+            WrappedString::Normal(s) => html! {{s}},
+            WrappedString::WithRuby(s) => html! {
+                { for s.iter()
+                    .map(|RubyString { value, ruby }| html! {
+                        <ruby>{value}<rt>{ruby}</rt></ruby>
+                    }) }
+            },
+            WrappedString::Secret { secret } => html! {
+                <span class="secret-value">
+                    <span class="mr-2" aria-hidden="true">{"\u{1f512}"}</span>
+                    { secret.hint.as_deref().unwrap_or("secret") }
+                    <button type="button" class="btn btn-link btn-sm align-baseline ml-2 p-0" onclick=self.props.request_unlock.reform(|_| ())>
+                        { "unlock to view" }
+                    </button>
+                </span>
+            },
+            WrappedString::Asset { asset } => html! {
+                <SealedAssetView asset=asset.clone() encrypt_key=self.props.encrypt_key.clone() request_unlock=self.props.request_unlock.clone()/>
+            },
         }
     }
 }
@@ -1,91 +1,77 @@
 use std::ops::Deref;
+use std::rc::Rc;
 
-use anyhow::Error;
-use serde::Deserialize;
-use yew::format::Json;
+use portfolio_model::protected::MaybeSealed;
+use portfolio_model::qualification::{QualificationData, QualificationDocument};
 use yew::prelude::*;
-use yew::services::fetch::*;
 
-use crate::routes::request;
+use crate::assets;
+use crate::components::sealed_asset::SealedAssetView;
+use crate::protected::{ProtectedResource, locked_entries};
+use crate::session::UnlockKey;
+use crate::signed::FetchError;
 
 pub(crate) struct Qualifications {
+    props: QualificationProperties,
     link: ComponentLink<Self>,
-    task: FetchTask,
-    data: Vec<QualificationData>,
+    data: ProtectedResource<Vec<QualificationData>>,
 }
 
 pub(crate) enum QualificationMessage {
-    FetchQualificationData(Vec<QualificationData>),
-    None,
+    FetchQualificationData(Result<Rc<QualificationDocument>, FetchError>),
+    Retry,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct QualificationValueData {
-    name: String,
-    time: String,
-    link: Option<String>,
-}
-
-#[derive(Debug, PartialEq, Deserialize)]
-struct QualificationGroupData {
-    name: String
-}
-
-#[derive(Debug, PartialEq, Deserialize)]
-pub(crate) struct QualificationData {
-    category: QualificationGroupData,
-    values: Vec<QualificationValueData>,
+#[derive(Clone, Debug, Default, PartialEq, Properties)]
+pub(crate) struct QualificationProperties {
+    #[prop_or_default]
+    pub(crate) encrypt_key: Option<UnlockKey>,
+    #[prop_or_default]
+    pub(crate) request_unlock: Callback<()>,
 }
 
 impl Component for Qualifications {
     type Message = QualificationMessage;
-    type Properties = ();
+    type Properties = QualificationProperties;
 
-    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let callback = link.callback(|response: Response<Json<Result<Vec<QualificationData>, Error>>>| {
-            if response.status().is_success() {
-                match response.into_body() {
-                    Json(Ok(s)) => {
-                        QualificationMessage::FetchQualificationData(s)
-                    }
-                    Json(Err(e)) => {
-                        log::error!("error in fetching link.data.json: {:?}", e);
-                        QualificationMessage::None
-                    }
-                }
-            } else {
-                log::error!("error in fetching link.data.json code: {}", response.status());
-                QualificationMessage::None
-            }
-        });
-        let task = request("/qualification.data.dc54e79d.json", callback);
-        Self { link, task, data: Vec::new() }
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut data = ProtectedResource::fetch(assets::QUALIFICATION_DATA_JSON, assets::QUALIFICATION_DATA_JSON_SIG, &link, QualificationMessage::FetchQualificationData);
+        data.unlock(props.encrypt_key.as_ref());
+        Self { props, link, data }
     }
 
     fn update(&mut self, msg: Self::Message) -> bool {
         match msg {
             QualificationMessage::FetchQualificationData(data) => {
                 log::info!("{:?}", data);
-                if self.data != data {
-                    self.data = data;
-                    true
-                } else {
-                    false
-                }
+                self.data.loaded(data, self.props.encrypt_key.as_ref());
+                true
+            }
+            QualificationMessage::Retry => {
+                self.data.retry();
+                true
             }
-            QualificationMessage::None => false,
         }
     }
 
-    fn change(&mut self, _props: Self::Properties) -> bool {
-        false
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            if self.props.encrypt_key != props.encrypt_key {
+                self.data.unlock(props.encrypt_key.as_ref());
+            }
+            self.props = props;
+            true
+        } else {
+            false
+        }
     }
 
     fn view(&self) -> Html {
-        let link_table = self.data
+        let link_table = self.data.data().map(Vec::as_slice).unwrap_or_default()
             .iter()
             .map(|data| {
-                let data_list = data.values.iter().map(|data| {
+                let locked = data.values.iter().filter(|data| data.public().is_none()).count();
+                let data_list = data.values.iter().filter_map(MaybeSealed::public).map(|data| {
                     let name = if let Some(link) = &data.link {
                         html! {
                             <div class="col h4"><a href={link.deref()} target="_blank">{data.name.deref()}</a></div>
@@ -99,6 +85,11 @@ impl Component for Qualifications {
                         <div class="row col-12 mt-3 ml-2">
                             <div class="col-12 col-lg-4 col-xl-3 h4">{data.time.deref()}</div>
                             {name}
+                            { for data.certificate.iter().map(|certificate| html! {
+                                <div class="col-12 col-lg-4 col-xl-3 h5">
+                                    <SealedAssetView asset=certificate.clone() encrypt_key=self.props.encrypt_key.clone() request_unlock=self.props.request_unlock.clone()/>
+                                </div>
+                            }) }
                         </div>
                     }
                 });
@@ -108,12 +99,15 @@ impl Component for Qualifications {
                             {data.category.name.deref()}
                         </h3>
                         {for data_list}
+                        <div class="col-12 ml-2">{ locked_entries(locked, &self.props.request_unlock) }</div>
                     </div>
                 }
             });
         html! {
             <>
                 <h1 class="m-2">{"Qualifications"}</h1>
+                { self.data.status_view(&self.link.callback(|_| QualificationMessage::Retry)) }
+                { self.data.error_view("qualifications") }
                 {for link_table}
             </>
         }
@@ -1,28 +1,40 @@
 use std::cell::RefCell;
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::Deref;
 use std::rc::Rc;
 use std::sync::RwLock;
 
-use anyhow::Error;
 use once_cell::sync::{Lazy, OnceCell};
-use serde::Deserialize;
+use portfolio_model::works::WorksData;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::Closure;
 use web_sys::{Element, HtmlElement, Node, SvgsvgElement};
-use yew::format::Json;
 use yew::prelude::*;
-use yew::services::fetch::*;
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
+use yew_router::prelude::*;
+use yew_router::route::Route;
+use yew_router::switch::AllowMissing;
 
-use crate::routes::request;
+use crate::assets;
+use crate::resource::Resource;
+use crate::routes::not_found::NotFound;
+use crate::routes::{AppRoute, decode_segment, encode_segment};
+use crate::signed::FetchError;
+use crate::store::{self, Subscription};
 
 pub(crate) struct Works {
+    props: WorksProperties,
     link: ComponentLink<Self>,
-    tasks: [FetchTask; 2],
-    works_data: Option<WorksData>,
+    route_dispatcher: RouteAgentDispatcher<()>,
+    works_data: Resource<WorksData>,
+    _svg_subscription: Subscription,
     works_svg: Option<String>,
     node_ref: NodeRef,
+    detail_button: NodeRef,
+    /// resolved from `props.selection` once the data is in
     selected_node: SelectedNode,
+    /// the page was opened with a selection, whose detail is shown once it is resolved
+    open_detail: bool,
 
     repository_nodes: Vec<Element>,
     language_nodes: Vec<Element>,
@@ -38,104 +50,116 @@ pub(crate) struct Works {
     technology_connected_repositories: Vec<BTreeSet<usize>>,
 }
 
-pub(crate) enum WorkMessage {
-    FetchWorksData(WorksData),
-    FetchWorksSvg(String),
-    UpdateSelectedNode(SelectedNode),
-    None,
+#[derive(Clone, Debug, Default, PartialEq, Properties)]
+pub(crate) struct WorksProperties {
+    #[prop_or_default]
+    pub(crate) selection: Option<WorksSelection>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub(crate) enum SelectedNode {
-    Repository(usize),
-    Language(usize),
-    Technology(usize),
-    None,
+/// Node of the graph in the route, by name as the indices change whenever the data is generated again.
+/// The name is kept percent-encoded, as it is in the URL.
+#[derive(Switch, Debug, Clone, PartialEq)]
+pub enum WorksSelection {
+    #[to = "/repository/{name}"]
+    Repository(String),
+    #[to = "/language/{name}"]
+    Language(String),
+    #[to = "/technology/{name}"]
+    Technology(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-pub struct Repository {
-    pub html_url: String,
-    pub name: String,
-    pub homepage: Option<String>,
-    pub language: Vec<(usize, usize)>,
-    pub community_profile: CommunityProfile,
-    pub technology_stacks: Vec<usize>,
-    pub related_repositories: Vec<usize>,
-}
+impl WorksSelection {
+    fn name(&self) -> String {
+        match self {
+            WorksSelection::Repository(name) | WorksSelection::Language(name) | WorksSelection::Technology(name) => decode_segment(name),
+        }
+    }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-pub struct CommunityProfile {
-    health_percentage: usize,
-    description: Option<String>,
-    documentation: Option<String>,
-    files: CommunityProfileFiles,
-}
+    /// The segment of the route before the name
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            WorksSelection::Repository(_) => "repository",
+            WorksSelection::Language(_) => "language",
+            WorksSelection::Technology(_) => "technology",
+        }
+    }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-pub struct CommunityProfileFiles {
-    license: Option<HashMap<String, Option<String>>>,
-    readme: Option<HashMap<String, String>>,
+    fn node(&self, works: &WorksData) -> SelectedNode {
+        let name = self.name();
+        let node = match self {
+            WorksSelection::Repository(_) => works.repositories.iter().position(|r| r.name == name).map(SelectedNode::Repository),
+            WorksSelection::Language(_) => works.languages.iter().position(|l| l.name == name).map(SelectedNode::Language),
+            WorksSelection::Technology(_) => works.technologies.iter().position(|t| t.name == name).map(SelectedNode::Technology),
+        };
+        node.unwrap_or(SelectedNode::None)
+    }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
-pub struct Language {
-    pub name: String,
-    pub link: Option<String>,
-}
+impl SelectedNode {
+    /// Every node of the graph
+    pub(crate) fn all(works: &WorksData) -> impl Iterator<Item = SelectedNode> {
+        (0..works.repositories.len()).map(SelectedNode::Repository)
+            .chain((0..works.languages.len()).map(SelectedNode::Language))
+            .chain((0..works.technologies.len()).map(SelectedNode::Technology))
+    }
 
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
-pub struct TechnologyStack {
-    pub name: String,
-    pub link: Option<String>,
-    pub description: Option<String>,
+    pub(crate) fn name<'a>(&self, works: &'a WorksData) -> Option<&'a str> {
+        match *self {
+            SelectedNode::Repository(i) => works.repositories.get(i).map(|r| r.name.as_str()),
+            SelectedNode::Language(i) => works.languages.get(i).map(|l| l.name.as_str()),
+            SelectedNode::Technology(i) => works.technologies.get(i).map(|t| t.name.as_str()),
+            SelectedNode::None => None,
+        }
+    }
+
+    pub(crate) fn selection(&self, works: &WorksData) -> Option<WorksSelection> {
+        let name = encode_segment(self.name(works)?);
+        match self {
+            SelectedNode::Repository(_) => Some(WorksSelection::Repository(name)),
+            SelectedNode::Language(_) => Some(WorksSelection::Language(name)),
+            SelectedNode::Technology(_) => Some(WorksSelection::Technology(name)),
+            SelectedNode::None => None,
+        }
+    }
 }
 
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
-pub struct WorksData {
-    pub repositories: Vec<Repository>,
-    pub languages: Vec<Language>,
-    pub technologies: Vec<TechnologyStack>,
+pub(crate) enum WorkMessage {
+    FetchWorksData(Result<Rc<WorksData>, FetchError>),
+    Retry,
+    FetchWorksSvg(String),
+    UpdateSelectedNode(SelectedNode),
+    None,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SelectedNode {
+    Repository(usize),
+    Language(usize),
+    Technology(usize),
+    None,
+}
 
 impl Component for Works {
     type Message = WorkMessage;
-    type Properties = ();
+    type Properties = WorksProperties;
 
-    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let callback = link.callback(|response: Response<Json<Result<WorksData, Error>>>| {
-            if response.status().is_success() {
-                match response.into_body() {
-                    Json(Ok(s)) => return WorkMessage::FetchWorksData(s),
-                    Json(Err(e)) => log::error!("error in fetching works.data.json: {:?}", e),
-                }
-            } else {
-                log::error!("error in fetching works.data.json code: {}", response.status());
-            }
-            WorkMessage::None
-        });
-        let task = request("/works.data.9a90fd0b.json", callback);
-        let callback = link.callback(|response: Response<Result<Vec<u8>, Error>>| {
-            if response.status().is_success() {
-                match response.into_body().map(String::from_utf8) {
-                    Ok(Ok(s)) => return WorkMessage::FetchWorksSvg(s),
-                    Ok(Err(e)) => log::error!("error in fetching works.graph.svg: {:?}", e),
-                    Err(e) => log::error!("error in fetching works.graph.svg: {:?}", e),
-                }
-            } else {
-                log::error!("error in fetching works.graph.svg code: {}", response.status());
-            }
-            WorkMessage::None
-        });
-        let tasks = [task, request("/works.graph.83f45361.svg", callback)];
-        Self {
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let works_data = Resource::fetch(assets::WORKS_DATA_JSON, assets::WORKS_DATA_JSON_SIG, link.callback(WorkMessage::FetchWorksData));
+        let svg_subscription = store::subscribe(assets::WORKS_GRAPH_SVG, None, link.callback(|()| {
+            Self::loaded_svg().map(WorkMessage::FetchWorksSvg).unwrap_or(WorkMessage::None)
+        }));
+        let open_detail = props.selection.is_some();
+        let mut works = Self {
+            props,
             link,
-            tasks,
-            works_data: None,
-            works_svg: None,
+            route_dispatcher: RouteAgentDispatcher::new(),
+            works_data,
+            _svg_subscription: svg_subscription,
+            works_svg: Self::loaded_svg(),
             node_ref: Default::default(),
+            detail_button: Default::default(),
             selected_node: SelectedNode::None,
+            open_detail,
             repository_nodes: Default::default(),
             language_nodes: Default::default(),
             technology_nodes: Default::default(),
@@ -147,16 +171,29 @@ impl Component for Works {
             repository_connected_repositories: Default::default(),
             language_connected_repositories: Default::default(),
             technology_connected_repositories: Default::default(),
+        };
+        if let Some(works_data) = works.works_data.data().cloned() {
+            // in the store already
+            works.construct_connection(&works_data);
+            works.select();
         }
+        works
     }
 
     fn update(&mut self, msg: Self::Message) -> bool {
         match msg {
             WorkMessage::FetchWorksData(works) => {
-                self.construct_connection(&works);
-                self.works_data = Some(works);
+                if let Ok(works) = &works {
+                    self.construct_connection(works);
+                }
+                self.works_data.loaded(works);
+                self.select();
                 self.render_svg();
-                false
+                true
+            }
+            WorkMessage::Retry => {
+                self.works_data.retry();
+                true
             }
             WorkMessage::FetchWorksSvg(svg) => {
                 self.works_svg = Some(svg);
@@ -164,29 +201,38 @@ impl Component for Works {
                 false
             }
             WorkMessage::UpdateSelectedNode(n) => {
-                if self.selected_node == n { return false; }
-                self.selected_node = n;
-                self.coloring();
-                true
+                // through the route, so that the back button undoes it
+                let selection = match self.works_data.data() {
+                    Some(works) => n.selection(works),
+                    None => return false,
+                };
+                if selection != self.props.selection {
+                    let route = AppRoute::Works(AllowMissing(selection));
+                    self.route_dispatcher.send(RouteRequest::ChangeRoute(Route::from(route)));
+                }
+                false
             }
             WorkMessage::None => false,
         }
     }
 
-    fn change(&mut self, _props: Self::Properties) -> bool {
-        false
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props == props { return false; }
+        self.props = props;
+        self.select()
     }
 
     fn view(&self) -> Html {
-        let modal_title = match self.selected_node {
-            SelectedNode::Repository(i) => self.works_data.as_ref().and_then(|w| w.repositories.get(i)).map(|r| r.name.as_str()).unwrap_or_default(),
-            SelectedNode::Language(i) => self.works_data.as_ref().and_then(|w| w.languages.get(i)).map(|r| r.name.as_str()).unwrap_or_default(),
-            SelectedNode::Technology(i) => self.works_data.as_ref().and_then(|w| w.technologies.get(i)).map(|r| r.name.as_str()).unwrap_or_default(),
-            SelectedNode::None => "",
-        };
+        if let (Some(selection), Some(_)) = (&self.props.selection, self.works_data.data()) {
+            if self.selected_node == SelectedNode::None {
+                // a stale link, to a node that has been renamed or removed
+                return html! { <NotFound path=AppRoute::Works(AllowMissing(Some(selection.clone()))).path()/> };
+            }
+        }
+        let modal_title = self.works_data.data().and_then(|w| self.selected_node.name(w)).unwrap_or_default();
         let url_regex = regex::Regex::new("^https?://").unwrap();
         let modal_body = match self.selected_node {
-            SelectedNode::Repository(i) => if let Some(WorksData { repositories, languages, technologies }) = self.works_data.as_ref() {
+            SelectedNode::Repository(i) => if let Some(WorksData { repositories, languages, technologies, .. }) = self.works_data.data() {
                 let repo = &repositories[i];
                 html! {
                     <>
@@ -238,7 +284,7 @@ impl Component for Works {
                     </>
                 }
             } else { html! {} },
-            SelectedNode::Language(i) => if let Some(WorksData { repositories, languages, technologies }) = self.works_data.as_ref() {
+            SelectedNode::Language(i) => if let Some(WorksData { repositories, languages, technologies, .. }) = self.works_data.data() {
                 let lang = &languages[i];
                 html! {
                     <>
@@ -260,7 +306,7 @@ impl Component for Works {
                     </>
                 }
             } else { html! {} },
-            SelectedNode::Technology(i) => if let Some(WorksData { repositories, languages, technologies }) = self.works_data.as_ref() {
+            SelectedNode::Technology(i) => if let Some(WorksData { repositories, languages, technologies, .. }) = self.works_data.data() {
                 let tech = &technologies[i];
                 html! {
                     <>
@@ -290,7 +336,8 @@ impl Component for Works {
         html! {
             <>
                 <h1 class="m-2">{"Works"}</h1>
-                <button type="button" class ="btn btn-secondary" data-toggle="modal" data-target="#exampleModal" disabled={self.selected_node == SelectedNode::None}>{ "show detail" }</button>
+                { self.works_data.status_view(&self.link.callback(|_| WorkMessage::Retry)) }
+                <button ref=self.detail_button.clone() type="button" class ="btn btn-secondary" data-toggle="modal" data-target="#exampleModal" disabled={self.selected_node == SelectedNode::None}>{ "show detail" }</button>
                 <div class="modal" id="exampleModal" tabindex="-1" role="dialog" aria-labelledby="exampleModalLabel" aria-hidden="true">
                     <div class="modal-dialog modal-dialog-centered" role="document">
                         <div class="modal-content">
@@ -318,10 +365,41 @@ impl Component for Works {
 
     fn rendered(&mut self, _first_render: bool) {
         self.render_svg();
+        if self.open_detail && self.works_data.data().is_some() {
+            self.open_detail = false;
+            if self.selected_node != SelectedNode::None {
+                // bootstrap opens the modal on the click of its toggle
+                if let Some(button) = self.detail_button.cast::<HtmlElement>() {
+                    button.click();
+                }
+            }
+        }
     }
 }
 
 impl Works {
+    /// works.graph.svg if the store has it
+    fn loaded_svg() -> Option<String> {
+        match store::bytes(assets::WORKS_GRAPH_SVG)? {
+            Ok(svg) => String::from_utf8(svg.to_vec()).map_err(|e| log::error!("error in fetching works.graph.svg: {:?}", e)).ok(),
+            Err(_) => None,
+        }
+    }
+
+    /// Take the node named in the route, returns whether it changed
+    fn select(&mut self) -> bool {
+        let node = match (&self.props.selection, self.works_data.data()) {
+            (Some(selection), Some(works)) => selection.node(works),
+            _ => SelectedNode::None,
+        };
+        if self.selected_node == node { return false; }
+        self.selected_node = node;
+        if self.node_ref.get().and_then(|node| node.first_child()).is_some() {
+            self.coloring();
+        }
+        true
+    }
+
     fn reset_all_color(&self) {
         self.repository_nodes.iter()
             .chain(self.language_nodes.iter())
@@ -403,7 +481,7 @@ impl Works {
         self.technology_connected_repositories = technology_connected_repositories;
     }
     fn construct_elements(&mut self, svg: &SvgsvgElement) {
-        let WorksData { repositories, languages, technologies } = self.works_data.as_ref().unwrap();
+        let WorksData { repositories, languages, technologies, .. } = self.works_data.data().unwrap();
         let mut repository_nodes = Vec::new();
         let mut language_nodes = Vec::new();
         let mut technology_nodes = Vec::new();
@@ -485,7 +563,7 @@ impl Works {
     }
     fn render_svg(&mut self) {
         let works_svg = if let Some(works_svg) = &self.works_svg { works_svg } else { return; };
-        let works_data = if let Some(works_data) = &self.works_data { works_data } else { return; };
+        let works_data = if let Some(works_data) = self.works_data.data() { works_data } else { return; };
         let node = if let Some(node) = self.node_ref.get() { node } else { return; };
         if let Some(_) = node.first_child() { return; }
         let parser = web_sys::DomParser::new().expect("failed to construct DomParser");
@@ -493,6 +571,9 @@ impl Works {
         let doc = doc.root_element().expect("failed to get document_element");
         doc.set_attribute("width", "100%");
         self.set_action(&doc);
+        if self.selected_node != SelectedNode::None {
+            self.coloring();
+        }
 
         node.append_child(doc.as_ref());
     }
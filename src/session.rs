@@ -0,0 +1,310 @@
+use std::cell::{Cell, RefCell};
+use std::convert::TryInto;
+use std::rc::Rc;
+use std::time::Duration;
+
+use portfolio_crypto::{KdfParams, Key, KeySource, Passphrase, SALT_SIZE};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use yew::format::{Json, Text};
+use yew::prelude::*;
+use yew::services::interval::IntervalTask;
+use yew::services::storage::{Area, StorageService};
+use yew::services::IntervalService;
+use yew::utils::{document, window};
+
+use crate::components::unlock_dialog::Throttle;
+
+/// Keys derived from the passphrase, never the passphrase itself
+const STORAGE_KEY: &str = "portfolio.derived-keys";
+/// Kept whether the key is remembered or not
+const THROTTLE_KEY: &str = "portfolio.throttle";
+/// Parameter of the fragment carrying the passphrase of a shared link, `#profile?k=...`, or `/profile#k=...` with the `path-routing` feature,
+/// which unlike the path and the query is never sent to the server
+const LINK_KEY_PARAM: &str = "k";
+/// Lock again after this long without any input
+const IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const ACTIVITY_EVENTS: [&str; 3] = ["pointerdown", "keydown", "scroll"];
+
+/// Unlock key of the current tab.
+/// Its derived keys are kept in `sessionStorage` only if asked to, and it is dropped after [`IDLE_TIMEOUT`] of inactivity.
+pub(crate) struct Session {
+    key: Option<UnlockKey>,
+    /// told when the key derives another key, which is to be remembered
+    key_derived: Callback<()>,
+    remember: bool,
+    throttle: Throttle,
+    storage: Option<StorageService>,
+    last_activity: Rc<Cell<f64>>,
+    _listeners: Vec<ActivityListener>,
+    _idle_check: IntervalTask,
+}
+
+struct ActivityListener {
+    event: &'static str,
+    closure: Closure<dyn FnMut()>,
+}
+
+impl Drop for ActivityListener {
+    fn drop(&mut self) {
+        let _ = document().remove_event_listener_with_callback(self.event, self.closure.as_ref().unchecked_ref());
+    }
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Session")
+            .field("key", &self.key)
+            .field("remember", &self.remember)
+            .finish()
+    }
+}
+
+impl Session {
+    /// Restore a remembered key. `idle_check` is called periodically, answer it with [`Session::lock_if_idle`],
+    /// and `key_derived` whenever the key derives another one, answer it with [`Session::key_derived`].
+    pub(crate) fn new(idle_check: Callback<()>, key_derived: Callback<()>) -> Self {
+        let storage = StorageService::new(Area::Session)
+            .map_err(|e| log::warn!("sessionStorage is not available: {}", e))
+            .ok();
+        let key = storage.as_ref()
+            .and_then(|storage| storage.restore::<Text>(STORAGE_KEY).ok())
+            .and_then(|derived| decode_derived(&derived))
+            .map(|derived| UnlockKey::new(None, derived, key_derived.clone()));
+        let remember = key.is_some();
+        let throttle = storage.as_ref()
+            .and_then(|storage| {
+                let Json(throttle) = storage.restore::<Json<Result<Throttle, anyhow::Error>>>(THROTTLE_KEY);
+                throttle.ok()
+            })
+            .unwrap_or_default();
+
+        let last_activity = Rc::new(Cell::new(js_sys::Date::now()));
+        let listeners = ACTIVITY_EVENTS.iter()
+            .map(|&event| {
+                let last_activity = Rc::clone(&last_activity);
+                let closure = Closure::wrap(Box::new(move || last_activity.set(js_sys::Date::now())) as Box<dyn FnMut()>);
+                document().add_event_listener_with_callback(event, closure.as_ref().unchecked_ref()).expect("failed to add event listener");
+                ActivityListener { event, closure }
+            })
+            .collect();
+        let idle_check = IntervalService::spawn(IDLE_CHECK_INTERVAL, idle_check);
+
+        Session { key, key_derived, remember, throttle, storage, last_activity, _listeners: listeners, _idle_check: idle_check }
+    }
+
+    pub(crate) fn key(&self) -> Option<&UnlockKey> {
+        self.key.as_ref()
+    }
+
+    pub(crate) fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    pub(crate) fn remember(&self) -> bool {
+        self.remember
+    }
+
+    /// Key of a submitted passphrase, to be checked before it replaces the key of the session with [`Session::unlock`]
+    pub(crate) fn candidate(&self, passphrase: Passphrase) -> UnlockKey {
+        UnlockKey::new(Some(passphrase), Vec::new(), self.key_derived.clone())
+    }
+
+    pub(crate) fn unlock(&mut self, key: UnlockKey) {
+        self.last_activity.set(js_sys::Date::now());
+        self.key = Some(key);
+        self.persist();
+    }
+
+    pub(crate) fn key_derived(&mut self) {
+        self.persist();
+    }
+
+    pub(crate) fn throttle(&self) -> &Throttle {
+        &self.throttle
+    }
+
+    /// The submitted passphrase is wrong
+    pub(crate) fn unlock_failed(&mut self) {
+        self.throttle.fail();
+        self.persist_throttle();
+    }
+
+    pub(crate) fn unlock_succeeded(&mut self) {
+        self.throttle.succeed();
+        self.persist_throttle();
+    }
+
+    pub(crate) fn lock(&mut self) -> ShouldRender {
+        let was_unlocked = self.key.take().is_some();
+        self.persist();
+        was_unlocked
+    }
+
+    /// Keep the derived keys in `sessionStorage` so that the unlock survives a reload of this tab.
+    pub(crate) fn set_remember(&mut self, remember: bool) -> ShouldRender {
+        if self.remember == remember { return false; }
+        self.remember = remember;
+        self.persist();
+        true
+    }
+
+    pub(crate) fn lock_if_idle(&mut self) -> ShouldRender {
+        if self.key.is_some() && js_sys::Date::now() - self.last_activity.get() > IDLE_TIMEOUT.as_millis() as f64 {
+            log::info!("locked after {} minutes of inactivity", IDLE_TIMEOUT.as_secs() / 60);
+            self.lock()
+        } else {
+            false
+        }
+    }
+
+    fn persist(&mut self) {
+        let storage = match &mut self.storage {
+            Some(storage) => storage,
+            None => return,
+        };
+        match (&self.key, self.remember) {
+            (Some(key), true) => storage.store(STORAGE_KEY, Text::Ok(encode_derived(&key.0.derived.borrow()))),
+            _ => storage.remove(STORAGE_KEY),
+        }
+    }
+
+    fn persist_throttle(&mut self) {
+        if let Some(storage) = &mut self.storage {
+            storage.store(THROTTLE_KEY, Json(&self.throttle));
+        }
+    }
+}
+
+/// Passphrase while it is at hand, and the keys derived from it for the [`KdfParams`] of each recipient slot tried so far.
+/// A key restored after a reload has only the derived keys, and opens the slots they were derived for.
+#[derive(Clone)]
+pub(crate) struct UnlockKey(Rc<UnlockKeyInner>);
+
+struct UnlockKeyInner {
+    passphrase: Option<Passphrase>,
+    derived: RefCell<Vec<(KdfParams, Key)>>,
+    derived_callback: Callback<()>,
+}
+
+impl UnlockKey {
+    fn new(passphrase: Option<Passphrase>, derived: Vec<(KdfParams, Key)>, derived_callback: Callback<()>) -> Self {
+        UnlockKey(Rc::new(UnlockKeyInner { passphrase, derived: RefCell::new(derived), derived_callback }))
+    }
+
+    /// The first of `params` no key is derived for yet, `None` if there is none or no passphrase to derive it from
+    pub(crate) fn underived<'a>(&self, params: &'a [KdfParams]) -> Option<&'a KdfParams> {
+        self.0.passphrase.as_ref()?;
+        let derived = self.0.derived.borrow();
+        params.iter().find(|&params| derived.iter().all(|(derived_params, _)| derived_params != params))
+    }
+
+    /// The keys derived so far, which pass over the slots of any other [`KdfParams`] instead of deriving for them
+    pub(crate) fn derived(&self) -> DerivedKeys<'_> {
+        DerivedKeys(self)
+    }
+
+    fn derived_key(&self, params: &KdfParams) -> Option<Key> {
+        self.0.derived.borrow().iter().find(|(derived, _)| derived == params).map(|(_, key)| key.clone())
+    }
+}
+
+impl KeySource for UnlockKey {
+    fn key_for(&self, params: &KdfParams) -> Option<Key> {
+        if let Some(key) = self.derived_key(params) {
+            return Some(key);
+        }
+        let key = self.0.passphrase.as_ref()?.derive_key(params);
+        self.0.derived.borrow_mut().push((params.clone(), key.clone()));
+        self.0.derived_callback.emit(());
+        Some(key)
+    }
+}
+
+/// See [`UnlockKey::derived`]
+pub(crate) struct DerivedKeys<'a>(&'a UnlockKey);
+
+impl KeySource for DerivedKeys<'_> {
+    fn key_for(&self, params: &KdfParams) -> Option<Key> {
+        self.0.derived_key(params)
+    }
+}
+
+/// The same key, not merely one derived from the same passphrase
+impl PartialEq for UnlockKey {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for UnlockKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UnlockKey(..)")
+    }
+}
+
+/// One `<iterations> <salt> <key>` line per derived key, in hex
+fn encode_derived(derived: &[(KdfParams, Key)]) -> String {
+    derived.iter()
+        .map(|(params, key)| format!("{} {} {}\n", params.iterations, hex(&params.salt), hex(key.as_bytes())))
+        .collect()
+}
+
+/// `None` for nothing or anything malformed, which leaves the tab locked
+fn decode_derived(text: &str) -> Option<Vec<(KdfParams, Key)>> {
+    let derived = text.lines()
+        .map(|line| {
+            let mut fields = line.split(' ');
+            let iterations = fields.next()?.parse().ok()?;
+            let salt: [u8; SALT_SIZE] = unhex(fields.next()?)?.try_into().ok()?;
+            let key: [u8; 32] = unhex(fields.next()?)?.try_into().ok()?;
+            Some((KdfParams { salt, iterations }, Key::from(key)))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if derived.is_empty() { None } else { Some(derived) }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// Take the passphrase out of the fragment of a shared link, see [`LINK_KEY_PARAM`],
+/// and remove it from the address bar and the history entry so that it is not bookmarked or shared further.
+pub(crate) fn take_link_key() -> Option<Passphrase> {
+    let location = window().location();
+    let hash = location.hash().ok()?;
+    // the query of the route in the fragment, or the fragment itself
+    #[cfg(not(feature = "path-routing"))]
+    let (route, query) = hash.split_once('?')?;
+    #[cfg(feature = "path-routing")]
+    let (route, query) = ("", hash.strip_prefix('#')?);
+    let mut key = None;
+    let rest = query.split('&')
+        .filter(|param| match param.strip_prefix(LINK_KEY_PARAM).and_then(|value| value.strip_prefix('=')) {
+            Some(value) => {
+                key = js_sys::decode_uri_component(value).ok().map(String::from);
+                false
+            }
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    let key = key?;
+    let hash = match (rest.is_empty(), route) {
+        (true, route) => route.to_string(),
+        (false, "") => format!("#{}", rest.join("&")),
+        (false, route) => format!("{}?{}", route, rest.join("&")),
+    };
+    let url = format!("{}{}{}", location.pathname().unwrap_or_default(), location.search().unwrap_or_default(), hash);
+    if let Err(e) = window().history().and_then(|history| history.replace_state_with_url(&JsValue::NULL, "", Some(&url))) {
+        log::error!("failed to remove the key from the URL: {:?}", e);
+    }
+    Some(Passphrase::new(&key))
+}
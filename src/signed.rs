@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+use anyhow::Error;
+use once_cell::sync::Lazy;
+use portfolio_crypto::VerifyingKey;
+use serde::de::DeserializeOwned;
+use yew::format::{Binary, Json};
+use yew::prelude::*;
+use yew::services::fetch::*;
+
+use crate::cache::Validators;
+use crate::routes::request;
+
+/// Base64 public key the data files are signed with by `encrypter sign`, given at build time.
+/// A release build does not compile without it, unless the `unsigned-dev` feature says it is not to be deployed.
+#[cfg(not(any(debug_assertions, feature = "unsigned-dev")))]
+const SIGNING_KEY_BASE64: Option<&str> = Some(env!(
+    "PORTFOLIO_SIGNING_KEY",
+    "set PORTFOLIO_SIGNING_KEY to the public key printed by `encrypter keygen`, or enable the `unsigned-dev` feature for a build whose data files are not verified"
+));
+/// Debug and `unsigned-dev` builds check signatures only when given the key, so that they run on unsigned data files
+#[cfg(any(debug_assertions, feature = "unsigned-dev"))]
+const SIGNING_KEY_BASE64: Option<&str> = option_env!("PORTFOLIO_SIGNING_KEY");
+
+static SIGNING_KEY: Lazy<Option<VerifyingKey>> = Lazy::new(|| {
+    SIGNING_KEY_BASE64.map(|key| VerifyingKey::decode(key).expect("PORTFOLIO_SIGNING_KEY is not a valid public key"))
+});
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum FetchError {
+    Failed,
+    /// the signature is missing or does not match, the file may have been tampered with
+    BadSignature,
+    Invalid,
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchError::Failed => write!(f, "failed to fetch"),
+            FetchError::BadSignature => write!(f, "signature verification failed"),
+            FetchError::Invalid => write!(f, "invalid data"),
+        }
+    }
+}
+
+/// A file as it came from the server
+#[derive(Clone, Debug)]
+pub(crate) struct Fetched {
+    pub(crate) data: Vec<u8>,
+    pub(crate) validators: Validators,
+}
+
+/// Fetch of a data file together with its detached signature
+#[derive(Debug)]
+pub(crate) struct SignedFetch {
+    _tasks: Vec<FetchTask>,
+}
+
+struct Pending {
+    /// `Ok(None)` if the copy at hand is current
+    data: Option<Result<Option<Fetched>, FetchError>>,
+    signature: Option<Result<Vec<u8>, FetchError>>,
+    /// whether `signature` is awaited
+    signed: bool,
+    callback: Option<Callback<Result<Option<Fetched>, FetchError>>>,
+}
+
+impl Pending {
+    fn finish(&mut self, addr: &str) {
+        let result = match (&self.data, &self.signature, &*SIGNING_KEY) {
+            (Some(Err(e)), _, _) => Err(e.clone()),
+            // nothing new to verify, the signature is not waited for
+            (Some(Ok(None)), _, _) => Ok(None),
+            (Some(Ok(Some(fetched))), _, None) => Ok(Some(fetched)),
+            (Some(Ok(Some(fetched))), _, Some(_)) if !self.signed => Ok(Some(fetched)),
+            (Some(Ok(Some(fetched))), Some(signature), Some(key)) => {
+                let verified = signature.as_ref().map_err(|_| FetchError::BadSignature).and_then(|signature| {
+                    let signature = std::str::from_utf8(signature).map_err(|_| FetchError::BadSignature)?;
+                    key.verify(&fetched.data, signature).map_err(|e| {
+                        log::error!("error in verifying {}: {}", addr, e);
+                        FetchError::BadSignature
+                    })
+                });
+                verified.map(|_| Some(fetched))
+            }
+            _ => return,
+        };
+        let result = result.map(|fetched| fetched.cloned());
+        if let Some(callback) = self.callback.take() {
+            callback.emit(result);
+        }
+    }
+}
+
+fn fetch_part(addr: &str, validators: Option<&Validators>, pending: &Rc<RefCell<Pending>>, data_addr: &str, store: fn(&mut Pending, Result<Option<Fetched>, FetchError>)) -> FetchTask {
+    let name = addr.to_string();
+    let data_addr = data_addr.to_string();
+    let pending = Rc::clone(pending);
+    request(addr, validators, Callback::from(move |response: Response<Binary>| {
+        let body = if response.status() == StatusCode::NOT_MODIFIED {
+            Ok(None)
+        } else if response.status().is_success() {
+            let validators = Validators::of(&response);
+            response.into_body()
+                .map(|data| Some(Fetched { data, validators }))
+                .map_err(|e| {
+                    log::error!("error in fetching {}: {:?}", name, e);
+                    FetchError::Failed
+                })
+        } else {
+            log::error!("error in fetching {} code: {}", name, response.status());
+            Err(FetchError::Failed)
+        };
+        let mut pending = pending.borrow_mut();
+        store(&mut pending, body);
+        pending.finish(&data_addr);
+    }))
+}
+
+/// Fetch the file `addr` and its signature `signature_addr`, and hand it over once the signature matches.
+/// The signature file is named as parcel would name it, `x.data.json.sig` becomes `/x.data.json.<hash>.sig`, see `site_builder signatures`.
+/// Without `signature_addr` the file is taken as it is, which is meant for files that are not signed, like the works graph.
+/// With the `validators` of a copy at hand, `callback` gets `Ok(None)` if that copy is current.
+pub(crate) fn fetch_signed(addr: &str, signature_addr: Option<&str>, validators: Option<&Validators>, callback: Callback<Result<Option<Fetched>, FetchError>>) -> SignedFetch {
+    let signed = signature_addr.is_some();
+    let pending = Rc::new(RefCell::new(Pending { data: None, signature: None, signed, callback: Some(callback) }));
+    let mut tasks = vec![fetch_part(addr, validators, &pending, addr, |pending, data| pending.data = Some(data))];
+    match signature_addr {
+        Some(signature_addr) if SIGNING_KEY.is_some() => {
+            // asked for in parallel even when revalidating, a round trip is longer than the few bytes of a signature
+            tasks.push(fetch_part(signature_addr, None, &pending, addr, |pending, signature| {
+                pending.signature = Some(signature.and_then(|fetched| fetched.map(|fetched| fetched.data).ok_or(FetchError::Failed)));
+            }));
+        }
+        _ => {}
+    }
+    SignedFetch { _tasks: tasks }
+}
+
+/// Parse the JSON data file `addr`
+pub(crate) fn parse<T: DeserializeOwned>(addr: &str, data: &[u8]) -> Result<T, FetchError> {
+    let Json(parsed) = Json::<Result<T, Error>>::from(Ok::<_, Error>(data.to_vec()));
+    parsed.map_err(|e| {
+        log::error!("error in fetching {}: {:?}", addr, e);
+        FetchError::Invalid
+    })
+}
@@ -0,0 +1,220 @@
+//! App-wide store of the fetched files. Each file is fetched once per session and shared by every page,
+//! so coming back to a page shows its data at once.
+//!
+//! A file kept in the [cache](crate::cache) by an earlier visit is shown right away and revalidated in the background.
+//! If the server has a newer one, it is saved for the next load and [`on_update_available`] callbacks are called,
+//! while the pages keep the copy they show.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use serde::de::DeserializeOwned;
+use yew::Callback;
+
+use crate::cache::{self, Validators};
+use crate::signed::{FetchError, Fetched, SignedFetch, fetch_signed, parse};
+
+thread_local! {
+    static STORE: RefCell<Store> = RefCell::new(Store::default());
+}
+
+#[derive(Default)]
+struct Store {
+    entries: HashMap<String, Entry>,
+    next_id: usize,
+    update_callbacks: Vec<Callback<()>>,
+}
+
+struct Entry {
+    signature_addr: Option<String>,
+    state: EntryState,
+    /// of the loaded contents, to revalidate them with
+    validators: Option<Validators>,
+    /// contents parsed by the last [`get`], kept for the next one
+    parsed: Option<Rc<dyn Any>>,
+    /// kept after it finished, as it is still running when the result comes in
+    task: Option<SignedFetch>,
+    subscribers: Vec<(usize, Callback<()>)>,
+}
+
+enum EntryState {
+    Loading,
+    Loaded(Rc<[u8]>),
+    Failed(FetchError),
+}
+
+impl Store {
+    fn entry(&mut self, addr: &str, signature_addr: Option<&str>) -> &mut Entry {
+        if !self.entries.contains_key(addr) {
+            let mut entry = Entry {
+                signature_addr: signature_addr.map(str::to_string),
+                state: EntryState::Loading,
+                validators: None,
+                parsed: None,
+                task: None,
+                subscribers: Vec::new(),
+            };
+            #[cfg(feature = "embedded-data")]
+            if let Some(data) = crate::embedded::file(addr) {
+                entry.state = EntryState::Loaded(Rc::from(data));
+            }
+            if let EntryState::Loading = entry.state {
+                if let Some((data, validators)) = cache::load(addr) {
+                    entry.state = EntryState::Loaded(Rc::from(data));
+                    entry.validators = Some(validators);
+                }
+                entry.start(addr);
+            }
+            self.entries.insert(addr.to_string(), entry);
+        }
+        self.entries.get_mut(addr).unwrap()
+    }
+}
+
+impl Entry {
+    /// Fetch the file, or revalidate the loaded contents, which are kept in any case
+    fn start(&mut self, addr: &str) {
+        let key = addr.to_string();
+        let validators = match self.state {
+            EntryState::Loaded(_) => self.validators.as_ref(),
+            EntryState::Loading | EntryState::Failed(_) => {
+                self.state = EntryState::Loading;
+                self.parsed = None;
+                None
+            }
+        };
+        self.task = Some(fetch_signed(addr, self.signature_addr.as_deref(), validators, Callback::from(move |result| finished(&key, result))));
+    }
+}
+
+fn finished(addr: &str, result: Result<Option<Fetched>, FetchError>) {
+    let callbacks = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let Store { entries, update_callbacks, .. } = &mut *store;
+        let entry = match entries.get_mut(addr) {
+            Some(entry) => entry,
+            None => return Vec::new(),
+        };
+        match (result, &entry.state) {
+            (Ok(None), _) => Vec::new(),
+            (Ok(Some(Fetched { data, validators })), EntryState::Loaded(current)) => {
+                cache::save(addr, &data, &validators);
+                entry.validators = Some(validators);
+                // the pages keep what they show, the new version is there after a reload
+                if **current != *data {
+                    log::info!("{} has changed", addr);
+                    update_callbacks.clone()
+                } else {
+                    Vec::new()
+                }
+            }
+            (Ok(Some(Fetched { data, validators })), _) => {
+                cache::save(addr, &data, &validators);
+                entry.state = EntryState::Loaded(Rc::from(data));
+                entry.validators = Some(validators);
+                entry.subscribers.iter().map(|(_, callback)| callback.clone()).collect()
+            }
+            (Err(e), EntryState::Loaded(_)) => {
+                log::warn!("{} is not revalidated, showing the copy kept before: {}", addr, e);
+                Vec::new()
+            }
+            (Err(e), _) => {
+                entry.state = EntryState::Failed(e);
+                entry.subscribers.iter().map(|(_, callback)| callback.clone()).collect()
+            }
+        }
+    });
+    // outside of the borrow, as the subscribers read the store right away
+    for callback in callbacks {
+        callback.emit(());
+    }
+}
+
+/// Notifies its callback of `addr` until dropped
+#[derive(Debug)]
+pub(crate) struct Subscription {
+    addr: String,
+    id: usize,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        STORE.with(|store| {
+            if let Some(entry) = store.borrow_mut().entries.get_mut(&self.addr) {
+                entry.subscribers.retain(|(id, _)| *id != self.id);
+            }
+        });
+    }
+}
+
+/// Fetch `addr` unless it is loaded or loading already, and call `callback` whenever a fetch of it finishes.
+/// A file that failed before is fetched again. See [`fetch_signed`] for `signature_addr`.
+pub(crate) fn subscribe(addr: &str, signature_addr: Option<&str>, callback: Callback<()>) -> Subscription {
+    let id = STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        store.next_id += 1;
+        let id = store.next_id;
+        let entry = store.entry(addr, signature_addr);
+        if let EntryState::Failed(_) = entry.state {
+            entry.start(addr);
+        }
+        entry.subscribers.push((id, callback));
+        id
+    });
+    Subscription { addr: addr.to_string(), id }
+}
+
+/// Call `callback` whenever a file turns out to have a newer version than the one shown
+pub(crate) fn on_update_available(callback: Callback<()>) {
+    STORE.with(|store| store.borrow_mut().update_callbacks.push(callback));
+}
+
+/// Fetch `addr` in advance, such as for the page behind a hovered link
+pub(crate) fn prefetch(addr: &str, signature_addr: Option<&str>) {
+    STORE.with(|store| {
+        store.borrow_mut().entry(addr, signature_addr);
+    });
+}
+
+/// Fetch `addr` again unless it is loading
+pub(crate) fn retry(addr: &str) {
+    STORE.with(|store| {
+        if let Some(entry) = store.borrow_mut().entries.get_mut(addr) {
+            if !matches!(entry.state, EntryState::Loading) {
+                entry.start(addr);
+            }
+        }
+    });
+}
+
+/// Contents of `addr`, `None` while it is loading
+pub(crate) fn bytes(addr: &str) -> Option<Result<Rc<[u8]>, FetchError>> {
+    STORE.with(|store| match &store.borrow().entries.get(addr)?.state {
+        EntryState::Loading => None,
+        EntryState::Loaded(data) => Some(Ok(Rc::clone(data))),
+        EntryState::Failed(e) => Some(Err(e.clone())),
+    })
+}
+
+/// JSON file `addr` parsed as `T`, `None` while it is loading
+pub(crate) fn get<T: DeserializeOwned + 'static>(addr: &str) -> Option<Result<Rc<T>, FetchError>> {
+    STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        let entry = store.entries.get_mut(addr)?;
+        let data = match &entry.state {
+            EntryState::Loading => return None,
+            EntryState::Loaded(data) => data,
+            EntryState::Failed(e) => return Some(Err(e.clone())),
+        };
+        if let Some(parsed) = entry.parsed.clone().and_then(|parsed| parsed.downcast::<T>().ok()) {
+            return Some(Ok(parsed));
+        }
+        let parsed = parse::<T>(addr, data).map(Rc::new);
+        if let Ok(parsed) = &parsed {
+            entry.parsed = Some(Rc::clone(parsed) as Rc<dyn Any>);
+        }
+        Some(parsed)
+    })
+}
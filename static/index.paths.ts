@@ -0,0 +1,16 @@
+// index.ts with the path-routing feature
+import {run} from '../crates/portfolio_path_routing/Cargo.toml';
+
+require("./link.data.json");
+require("./qualification.data.json");
+require("./license.data.json");
+require("./profile.data.json");
+require("./works.data.json");
+require("./works.graph.svg");
+
+require("./icon.svg");
+require("./icon.png");
+require("./Twitter_Logo_WhiteOnBlue.png");
+require("./GitHub-Mark-120px-plus.png");
+
+run()
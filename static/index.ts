@@ -4,7 +4,6 @@ require("./link.data.json");
 require("./qualification.data.json");
 require("./license.data.json");
 require("./profile.data.json");
-require("./profile.data.enc.bin");
 require("./works.data.json");
 require("./works.graph.svg");
 
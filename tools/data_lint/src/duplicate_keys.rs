@@ -0,0 +1,71 @@
+//! serde_json keeps the last of duplicate keys without a word, so they are found on a separate pass.
+
+use std::cell::RefCell;
+use std::fmt;
+
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+/// Paths of the keys that appear more than once in the same object of `json`
+pub(crate) fn find_duplicate_keys(json: &[u8]) -> serde_json::Result<Vec<String>> {
+    let duplicates = RefCell::new(Vec::new());
+    let seed = PathSeed { path: String::new(), duplicates: &duplicates };
+    seed.deserialize(&mut serde_json::Deserializer::from_slice(json))?;
+    Ok(duplicates.into_inner())
+}
+
+struct PathSeed<'a> {
+    path: String,
+    duplicates: &'a RefCell<Vec<String>>,
+}
+
+impl<'a> PathSeed<'a> {
+    fn child(&self, path: String) -> Self {
+        PathSeed { path, duplicates: self.duplicates }
+    }
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for PathSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for PathSeed<'a> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> { Ok(()) }
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> { Ok(()) }
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> { Ok(()) }
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> { Ok(()) }
+    fn visit_str<E>(self, _: &str) -> Result<(), E> { Ok(()) }
+    fn visit_unit<E>(self) -> Result<(), E> { Ok(()) }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        while seq.next_element_seed(self.child(format!("{}[{}]", self.path, index)))?.is_some() {
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut keys = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = if self.path.is_empty() { key.clone() } else { format!("{}.{}", self.path, key) };
+            if keys.contains(&key) {
+                self.duplicates.borrow_mut().push(path);
+                map.next_value::<IgnoredAny>()?;
+            } else {
+                map.next_value_seed(self.child(path))?;
+                keys.push(key);
+            }
+        }
+        Ok(())
+    }
+}
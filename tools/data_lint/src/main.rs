@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use clap::{App, Arg};
+use portfolio_assets::bundled_name;
+use portfolio_model::license::LicenseData;
+use portfolio_model::link::{LinkData, LinkKeyData, LinkValueData};
+use portfolio_model::profile::{ProfileData, WrappedString};
+use portfolio_model::protected::{MaybeSealed, Protected};
+use portfolio_model::qualification::QualificationData;
+use portfolio_model::works::WorksData;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use url::Url;
+
+use crate::duplicate_keys::find_duplicate_keys;
+
+mod duplicate_keys;
+
+fn main() {
+    let matches = App::new("data_lint")
+        .about("check every data file against the types the site reads it with")
+        .arg(Arg::with_name("static")
+            .long("static")
+            .takes_value(true)
+            .default_value("static")
+            .help("directory of the data files and the images they refer to"))
+        .get_matches();
+    let mut lint = Lint::new(Path::new(matches.value_of("static").unwrap()));
+    lint.protected_file("link.data.json", check_links);
+    lint.protected_file("qualification.data.json", check_qualifications);
+    lint.protected_file("profile.data.json", check_profile);
+    lint.protected_file("license.data.json", check_licenses);
+    lint.file("works.data.json", check_works);
+    if lint.problems > 0 {
+        eprintln!("{} problem{} found", lint.problems, if lint.problems == 1 { "" } else { "s" });
+        std::process::exit(1);
+    }
+}
+
+struct Lint {
+    dir: PathBuf,
+    /// Every file under `dir` by its path and by the name parcel serves it at
+    served: HashSet<String>,
+    problems: usize,
+}
+
+impl Lint {
+    fn new(dir: &Path) -> Self {
+        let mut served = HashSet::new();
+        collect_files(dir, dir, &mut served);
+        Lint { dir: dir.to_path_buf(), served, problems: 0 }
+    }
+
+    /// Check a plain data file, `check` gets the parsed contents
+    fn file<T: DeserializeOwned>(&mut self, name: &str, check: fn(T, &mut Report)) {
+        if let Some(value) = self.read(name) {
+            self.check_value(name, value, "", check);
+        }
+    }
+
+    /// Check a [`Protected`] data file, `check` gets the public data, the sealed parts cannot be checked
+    fn protected_file<T: DeserializeOwned>(&mut self, name: &str, check: fn(T, &mut Report)) {
+        let value = match self.read(name) {
+            Some(value) => value,
+            None => return,
+        };
+        if let Err(e) = serde_json::from_value::<Protected<Value>>(value.clone()) {
+            self.report(name, "").problem("", e);
+            return;
+        }
+        match value {
+            Value::Object(mut document) if document.contains_key("data") => {
+                let data = document.remove("data").unwrap();
+                self.check_value(name, data, "data", check)
+            }
+            bare => self.check_value(name, bare, "", check),
+        }
+    }
+
+    /// Parse `name`, reporting syntax errors and duplicate keys
+    fn read(&mut self, name: &str) -> Option<Value> {
+        let path = self.dir.join(name);
+        let content = match std::fs::read(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.report(name, "").problem("", format!("unable to read {}: {}", path.display(), e));
+                return None;
+            }
+        };
+        let duplicates = match find_duplicate_keys(&content) {
+            Ok(duplicates) => duplicates,
+            Err(e) => {
+                self.report(name, "").problem("", format!("invalid JSON: {}", e));
+                return None;
+            }
+        };
+        let mut report = self.report(name, "");
+        for key in duplicates {
+            report.problem(&key, "duplicate key, only the last value is used");
+        }
+        serde_json::from_slice(&content).ok()
+    }
+
+    fn check_value<T: DeserializeOwned>(&mut self, name: &str, value: Value, prefix: &str, check: fn(T, &mut Report)) {
+        let mut report = self.report(name, prefix);
+        match serde_path_to_error::deserialize::<_, T>(value) {
+            Ok(data) => check(data, &mut report),
+            Err(e) => {
+                let path = e.path().to_string();
+                report.problem(if path == "." { "" } else { &path }, e.inner());
+            }
+        }
+    }
+
+    fn report<'a>(&'a mut self, name: &'a str, prefix: &'a str) -> Report<'a> {
+        Report { file: name, prefix, served: &self.served, problems: &mut self.problems }
+    }
+}
+
+fn collect_files(root: &Path, dir: &Path, served: &mut HashSet<String>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_files(root, &path, served);
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap().components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        served.insert(bundled_name(&relative));
+        served.insert(relative);
+    }
+}
+
+/// Problems of one data file, printed as `<file>: <path>: <message>`
+struct Report<'a> {
+    file: &'a str,
+    /// path of the checked value inside the file
+    prefix: &'a str,
+    served: &'a HashSet<String>,
+    problems: &'a mut usize,
+}
+
+impl Report<'_> {
+    fn problem(&mut self, path: &str, message: impl Display) {
+        let path = match (self.prefix, path) {
+            ("", "") => ".".to_string(),
+            (prefix, "") => prefix.to_string(),
+            ("", path) => path.to_string(),
+            (prefix, path) if path.starts_with('[') => format!("{}{}", prefix, path),
+            (prefix, path) => format!("{}.{}", prefix, path),
+        };
+        eprintln!("{}: {}: {}", self.file, path, message);
+        *self.problems += 1;
+    }
+
+    fn url(&mut self, path: &str, url: &str) {
+        match Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            Ok(_) => self.problem(path, format!("\"{}\" is not an http(s) URL", url)),
+            Err(e) => self.problem(path, format!("\"{}\" is not a valid URL: {}", url, e)),
+        }
+    }
+
+    /// Optional URL, where an empty string means none as well
+    fn optional_url(&mut self, path: &str, url: Option<&String>) {
+        match url {
+            Some(url) if !url.is_empty() => self.url(path, url),
+            _ => {}
+        }
+    }
+
+    /// URL of a file that is either under the static directory or elsewhere on the web
+    fn static_file(&mut self, path: &str, url: &str) {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            self.url(path, url);
+        } else if !self.served.contains(url.trim_start_matches("./").trim_start_matches('/')) {
+            self.problem(path, format!("\"{}\" does not exist under static/", url));
+        }
+    }
+
+    fn index(&mut self, path: &str, index: usize, len: usize, of: &str) {
+        if index >= len {
+            self.problem(path, format!("index {} is out of range, there are {} {}", index, len, of));
+        }
+    }
+}
+
+fn check_links(links: Vec<MaybeSealed<LinkData>>, report: &mut Report) {
+    for (i, link) in links.iter().enumerate() {
+        let link = match link.public() {
+            Some(link) => link,
+            None => continue,
+        };
+        if let LinkKeyData::WithImage { image, .. } = &link.key {
+            report.static_file(&format!("[{}].key.image", i), image);
+        }
+        if let LinkValueData::WithLink { link, .. } = &link.value {
+            report.url(&format!("[{}].value.link", i), link);
+        }
+    }
+}
+
+fn check_qualifications(qualifications: Vec<QualificationData>, report: &mut Report) {
+    for (i, qualification) in qualifications.iter().enumerate() {
+        for (j, value) in qualification.values.iter().enumerate() {
+            let value = match value.public() {
+                Some(value) => value,
+                None => continue,
+            };
+            report.optional_url(&format!("[{}].values[{}].link", i, j), value.link.as_ref());
+            if let Some(certificate) = &value.certificate {
+                report.static_file(&format!("[{}].values[{}].certificate.path", i, j), &certificate.path);
+            }
+        }
+    }
+}
+
+fn check_profile(profile: Vec<ProfileData>, report: &mut Report) {
+    for (i, category) in profile.iter().enumerate() {
+        for (j, value) in category.values.iter().enumerate() {
+            if let WrappedString::Asset { asset } = &value.value {
+                report.static_file(&format!("[{}].values[{}].value.asset.path", i, j), &asset.path);
+            }
+        }
+    }
+}
+
+fn check_licenses(licenses: Vec<LicenseData>, report: &mut Report) {
+    for (i, license) in licenses.iter().enumerate() {
+        report.optional_url(&format!("[{}].repository", i), license.repository.as_ref());
+    }
+}
+
+fn check_works(works: WorksData, report: &mut Report) {
+    for (i, repository) in works.repositories.iter().enumerate() {
+        report.url(&format!("repositories[{}].html_url", i), &repository.html_url);
+        report.optional_url(&format!("repositories[{}].homepage", i), repository.homepage.as_ref());
+        report.optional_url(&format!("repositories[{}].community_profile.documentation", i), repository.community_profile.documentation.as_ref());
+        for (j, &(language, _)) in repository.language.iter().enumerate() {
+            report.index(&format!("repositories[{}].language[{}]", i, j), language, works.languages.len(), "languages");
+        }
+        for (j, &technology) in repository.technology_stacks.iter().enumerate() {
+            report.index(&format!("repositories[{}].technology_stacks[{}]", i, j), technology, works.technologies.len(), "technologies");
+        }
+        for (j, &related) in repository.related_repositories.iter().enumerate() {
+            report.index(&format!("repositories[{}].related_repositories[{}]", i, j), related, works.repositories.len(), "repositories");
+        }
+    }
+    for (i, language) in works.languages.iter().enumerate() {
+        report.optional_url(&format!("languages[{}].link", i), language.link.as_ref());
+    }
+    for (i, technology) in works.technologies.iter().enumerate() {
+        report.optional_url(&format!("technologies[{}].link", i), technology.link.as_ref());
+    }
+}
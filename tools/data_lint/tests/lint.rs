@@ -0,0 +1,92 @@
+//! Runs data_lint on a copy of `fixtures/valid` with one file replaced, and checks what it reports.
+
+use std::path::Path;
+use std::process::Command;
+
+const VALID: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/valid");
+
+/// Whether data_lint passes the fixture with `name` replaced by `content`, and the problems it printed
+fn lint(case: &str, name: &str, content: &str) -> (bool, String) {
+    let dir = std::env::temp_dir().join(format!("data_lint-{}-{}", case, std::process::id()));
+    copy_dir(Path::new(VALID), &dir);
+    std::fs::write(dir.join(name), content).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_data_lint")).arg("--static").arg(&dir).output().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    (output.status.success(), String::from_utf8(output.stderr).unwrap())
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        std::fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+    }
+}
+
+fn assert_reported(case: &str, name: &str, content: &str, expected: &str) {
+    let (passed, problems) = lint(case, name, content);
+    assert!(!passed, "passed with {}:\n{}", name, content);
+    assert!(problems.contains(expected), "expected {:?} in:\n{}", expected, problems);
+}
+
+#[test]
+fn valid_fixture_passes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_data_lint")).arg("--static").arg(VALID).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn schema_error_is_reported_at_its_path() {
+    let profile = r#"[{ "category_name": "Profile", "values": [{ "key": "Name", "value": 1 }] }]"#;
+    assert_reported("schema", "profile.data.json", profile, "profile.data.json: [0].values[0].value:");
+}
+
+#[test]
+fn schema_error_in_a_document_is_reported_under_data() {
+    let qualification = r#"{ "data": [{ "category": {}, "values": [] }] }"#;
+    assert_reported("document", "qualification.data.json", qualification, "qualification.data.json: data[0].category: missing field `name`");
+}
+
+#[test]
+fn document_of_another_version_is_reported() {
+    let licenses = r#"{ "format_version": 2, "data": [] }"#;
+    assert_reported("version", "license.data.json", licenses, "license.data.json: .: unsupported format version 2");
+}
+
+#[test]
+fn duplicate_key_is_reported() {
+    let link = r#"[{ "key": "Plain", "key": "Other", "value": "id" }]"#;
+    assert_reported("duplicate", "link.data.json", link, "link.data.json: [0].key: duplicate key");
+}
+
+#[test]
+fn url_that_is_not_http_is_reported() {
+    let link = r#"[{ "key": "Plain", "value": { "display": "mail", "link": "mailto:someone@example.com" } }]"#;
+    assert_reported("scheme", "link.data.json", link, "link.data.json: [0].value.link: \"mailto:someone@example.com\" is not an http(s) URL");
+}
+
+#[test]
+fn invalid_url_is_reported() {
+    let licenses = r#"[{ "name": "serde", "version": null, "authors": null, "repository": "github.com/serde-rs/serde", "license": null, "description": null }]"#;
+    assert_reported("url", "license.data.json", licenses, "license.data.json: [0].repository: \"github.com/serde-rs/serde\" is not a valid URL");
+}
+
+#[test]
+fn missing_image_is_reported() {
+    let link = r#"[{ "key": { "name": "Example", "image": "./missing.png" }, "value": "id" }]"#;
+    assert_reported("image", "link.data.json", link, "link.data.json: [0].key.image: \"./missing.png\" does not exist under static/");
+}
+
+#[test]
+fn image_by_its_bundled_name_is_found() {
+    let link = format!(r#"[{{ "key": {{ "name": "Example", "image": "/{}" }}, "value": "id" }}]"#, portfolio_assets::bundled_name("logo.png"));
+    let (passed, problems) = lint("bundled", "link.data.json", &link);
+    assert!(passed, "{}", problems);
+}
+
+#[test]
+fn technology_index_out_of_range_is_reported() {
+    let works = std::fs::read_to_string(Path::new(VALID).join("works.data.json")).unwrap()
+        .replace(r#""technology_stacks": [0]"#, r#""technology_stacks": [0, 1]"#);
+    assert_reported("index", "works.data.json", &works, "works.data.json: repositories[0].technology_stacks[1]: index 1 is out of range, there are 1 technologies");
+}
@@ -0,0 +1,34 @@
+//! The profile as encrypted before the sealed format, `profile.data.enc.bin`:
+//! the whole profile JSON, zero padded and encrypted with AES-256 whose key is the SHA3-256 of the passphrase as typed.
+//! There is no tag, so a wrong passphrase only shows as JSON that does not parse.
+
+use portfolio_model::profile::ProfileData;
+use sha3::{Digest, Sha3_256};
+
+fn key(passphrase: &str) -> aes::Key {
+    aes::Key::AES256(Sha3_256::digest(passphrase.as_bytes()).into())
+}
+
+/// Decrypt a legacy `profile.data.enc.bin`.
+pub(crate) fn open_legacy(data: &[u8], passphrase: &str) -> serde_json::Result<Vec<ProfileData>> {
+    let mut plaintext = aes::decrypt(data, &key(passphrase));
+    while plaintext.last() == Some(&0) {
+        plaintext.pop();
+    }
+    serde_json::from_slice(&plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key, open_legacy};
+
+    const PROFILE: &str = r#"[{"category_name":"Profile","values":[{"key":"氏名","value":"山田太郎"}]}]"#;
+
+    #[test]
+    fn open_with_the_passphrase_as_typed() {
+        let data = aes::encrypt(PROFILE.as_bytes(), &key("やまだたろう"));
+        let profile = open_legacy(&data, "やまだたろう").unwrap();
+        assert_eq!(serde_json::to_string(&profile).unwrap(), PROFILE);
+        assert!(open_legacy(&data, "ヤマダタロウ").is_err());
+    }
+}
@@ -1,28 +1,821 @@
-use std::convert::TryInto;
-use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Write;
 
-use clap::{App, Arg};
-use sha3::Digest;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use portfolio_crypto::{ASSET_KEY_ID, ASSET_MAGIC, KdfParams, Key, Keyring, Passphrase, RecipientKeys, SealedAsset, SigningKey, Validity, VerifyingKey, open_field, seal_field};
+use portfolio_model::FORMAT_VERSION;
+use portfolio_model::link::LinkData;
+use portfolio_model::profile::{ProfileData, ProfileDocument};
+use portfolio_model::protected::{MaybeSealed, Protected, Reveal, RevealError, SealedValue};
+use portfolio_model::qualification::QualificationData;
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::legacy::open_legacy;
+use crate::profile_source::{ProfileSourceData, legacy_source, public_profile, public_value_with_hint, secret_source};
+use crate::sealed_json::{AnyData, seal_secret_objects};
+use crate::time::{format_time, now, parse_time};
+
+mod legacy;
+mod profile_source;
+mod sealed_json;
+mod time;
+
+const WEAK_PASSPHRASE_BITS: f64 = 64.0;
+/// Characters a normalized passphrase is made of, hiragana `ぁ`..=`ゖ` and `ー`
+const PASSPHRASE_ALPHABET: u32 = 86 + 1;
+const DEFAULT_LABEL: &str = "owner";
+/// Data files whose revealed contents `verify` checks against the types the site reads them with
+const DATA_TYPES: [&str; 3] = ["profile", "qualification", "link"];
 
 fn main() {
+    let iterations = Arg::with_name("iterations")
+        .long("iterations")
+        .takes_value(true)
+        .required(false);
+    let label = Arg::with_name("label")
+        .long("label")
+        .takes_value(true)
+        .help("name of the recipient slot, shown as the unlock level on the site");
     let matches = App::new("encrypter")
-        .arg(Arg::with_name("key")
-            .takes_value(true)
-            .required(true))
-        .arg(Arg::with_name("from")
-            .takes_value(true)
-            .required(true))
-        .arg(Arg::with_name("to")
-            .takes_value(true)
-            .required(true))
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("encrypt")
+            .about("validate a profile JSON file and seal every value of it into a public profile")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("from")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("pretty")
+                .long("pretty")
+                .help("print the normalized profile JSON that is sealed"))
+            .arg(label.clone().default_value(DEFAULT_LABEL))
+            .arg(iterations.clone()))
+        .subcommand(SubCommand::with_name("publish")
+            .about("turn an annotated profile source into the public JSON with every secret value sealed in place")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("source")
+                .takes_value(true)
+                .required(true)
+                .help("profile JSON with \"visibility\": \"public\" | \"secret\" on every value, kept out of git as /*.enc.json"))
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(true))
+            .arg(label.clone().default_value(DEFAULT_LABEL))
+            .arg(iterations.clone()))
+        .subcommand(SubCommand::with_name("import-legacy")
+            .about("decrypt a profile.data.enc.bin made before the sealed format into a source for publish")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true)
+                .help("passphrase exactly as it was typed on the site, it was not normalized then"))
+            .arg(Arg::with_name("from")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("public")
+                .takes_value(true)
+                .required(true)
+                .help("published profile.data.json, whose placeholders tell which values are secret"))
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(true)
+                .help("profile source, kept out of git as /*.enc.json")))
+        .subcommand(SubCommand::with_name("seal")
+            .about("seal every object marked \"visibility\": \"secret\" in any data file, such as a link or qualification entry")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("source")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(true))
+            .arg(label.clone().default_value(DEFAULT_LABEL))
+            .arg(iterations.clone()))
+        .subcommand(SubCommand::with_name("seal-asset")
+            .about("seal a binary file such as a photo or a certificate as a whole, with its MIME type in the header")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("from")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(true)
+                .help("sealed file, named /*.enc.bin"))
+            .arg(Arg::with_name("mime")
+                .long("mime")
+                .takes_value(true)
+                .help("MIME type of <from>, guessed from its extension if omitted"))
+            .arg(label.clone().default_value(DEFAULT_LABEL))
+            .arg(iterations.clone()))
+        .subcommand(SubCommand::with_name("decrypt")
+            .about("reveal the parts <key> can open and write the data to <to> or stdout")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("from")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(false)))
+        .subcommand(SubCommand::with_name("verify")
+            .about("check that <key> opens <file> and that the revealed data is what the site reads, or that it opens a sealed asset")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("type")
+                .long("type")
+                .takes_value(true)
+                .possible_values(&DATA_TYPES)
+                .help("kind of data in <file>, taken from its name such as profile.data.json if omitted")))
+        .subcommand(SubCommand::with_name("rekey")
+            .about("change the passphrase of the recipient <key> belongs to without writing the plaintext to disk")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("new_key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(false))
+            .arg(iterations.clone()))
+        .subcommand(SubCommand::with_name("add-recipient")
+            .about("let <new_key> open the categories <key> can open, or the subset given by --categories")
+            .arg(Arg::with_name("key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("new_key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("categories")
+                .long("categories")
+                .takes_value(true)
+                .use_delimiter(true)
+                .help("comma separated category names"))
+            .arg(Arg::with_name("not_before")
+                .long("not-before")
+                .takes_value(true)
+                .help("unix seconds or UTC date YYYY-MM-DD from which <new_key> is accepted by the site"))
+            .arg(Arg::with_name("not_after")
+                .long("not-after")
+                .takes_value(true)
+                .help("unix seconds or UTC date YYYY-MM-DD, through the end of which <new_key> is accepted"))
+            .arg(Arg::with_name("link")
+                .long("link")
+                .takes_value(true)
+                .help("URL of the site, to print a link that unlocks it with <new_key>"))
+            .arg(Arg::with_name("path-routing")
+                .long("path-routing")
+                .requires("link")
+                .help("print the link for a site built with the path-routing feature, `/profile#k=...`"))
+            .arg(label.required(true))
+            .arg(iterations))
+        .subcommand(SubCommand::with_name("remove-recipient")
+            .about("remove a recipient slot")
+            .arg(Arg::with_name("file")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("label")
+                .takes_value(true)
+                .required(true)))
+        .subcommand(SubCommand::with_name("keygen")
+            .about("create the key the data files are signed with, and print its public key to build the site with")
+            .arg(Arg::with_name("to")
+                .takes_value(true)
+                .required(true)
+                .help("file to write the secret key to, kept out of git"))
+            .arg(Arg::with_name("force")
+                .long("force")
+                .help("overwrite an existing key")))
+        .subcommand(SubCommand::with_name("sign")
+            .about("write a detached signature <file>.sig for each data file")
+            .arg(Arg::with_name("secret")
+                .takes_value(true)
+                .required(true)
+                .help("file made by keygen"))
+            .arg(Arg::with_name("files")
+                .takes_value(true)
+                .required(true)
+                .multiple(true)))
+        .subcommand(SubCommand::with_name("verify-signature")
+            .about("check <file>.sig against <public_key>, as the site does before rendering")
+            .arg(Arg::with_name("public_key")
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name("files")
+                .takes_value(true)
+                .required(true)
+                .multiple(true)))
+        .subcommand(SubCommand::with_name("recipients")
+            .about("list recipient slots")
+            .arg(Arg::with_name("file")
+                .takes_value(true)
+                .required(true)))
         .get_matches();
-    let key = sha3::Sha3_256::digest(matches.value_of("key").unwrap().as_bytes()).to_vec().try_into().unwrap();
-    let key = aes::Key::AES256(key);
-    let mut from = File::open(matches.value_of("from").unwrap()).expect("unable to open from file");
-    let mut to = File::open(matches.value_of("to").unwrap()).or_else(|_| File::create(matches.value_of("to").unwrap())).expect("unable to open to file");
-
-    let mut vec = Vec::new();
-    from.read_to_end(&mut vec).expect("unable to read from file");
-    to.write_all(&aes::encrypt(&vec, &key)).expect("unable to write to file");
+
+    match matches.subcommand() {
+        ("encrypt", Some(matches)) => encrypt(matches),
+        ("publish", Some(matches)) => publish(matches),
+        ("import-legacy", Some(matches)) => import_legacy(matches),
+        ("seal", Some(matches)) => seal(matches),
+        ("seal-asset", Some(matches)) => seal_asset(matches),
+        ("decrypt", Some(matches)) => decrypt(matches),
+        ("verify", Some(matches)) => verify(matches),
+        ("rekey", Some(matches)) => rekey(matches),
+        ("add-recipient", Some(matches)) => add_recipient(matches),
+        ("remove-recipient", Some(matches)) => remove_recipient(matches),
+        ("keygen", Some(matches)) => keygen(matches),
+        ("sign", Some(matches)) => sign(matches),
+        ("verify-signature", Some(matches)) => verify_signature(matches),
+        ("recipients", Some(matches)) => recipients(matches),
+        _ => unreachable!(),
+    }
+}
+
+fn encrypt(matches: &ArgMatches) {
+    let path = matches.value_of("from").unwrap();
+    let data = std::fs::read(path).expect("unable to read from file");
+    let profile = parse_profile(&data).unwrap_or_else(|e| {
+        eprintln!("{}: invalid profile data: {}", path, e);
+        std::process::exit(1);
+    });
+    if matches.is_present("pretty") {
+        println!("{}", serde_json::to_string_pretty(&profile).unwrap());
+    }
+    let passphrase = new_passphrase(matches.value_of("key").unwrap());
+    let document = seal_profile(&secret_source(&profile), matches.value_of("label").unwrap(), &passphrase, kdf_params(matches));
+    write_document(matches.value_of("to").unwrap(), &document);
+}
+
+fn publish(matches: &ArgMatches) {
+    let path = matches.value_of("source").unwrap();
+    let data = std::fs::read(path).expect("unable to read source file");
+    let source: Vec<ProfileSourceData> = serde_json::from_slice(&data).unwrap_or_else(|e| {
+        eprintln!("{}: invalid profile source: {}", path, e);
+        std::process::exit(1);
+    });
+    if let Some(key) = public_value_with_hint(&source) {
+        eprintln!("{}: public value \"{}\" has a hint, which is only shown for secret values", path, key);
+        std::process::exit(1);
+    }
+    let passphrase = new_passphrase(matches.value_of("key").unwrap());
+    let document = seal_profile(&source, matches.value_of("label").unwrap(), &passphrase, kdf_params(matches));
+    write_document(matches.value_of("to").unwrap(), &document);
+}
+
+fn import_legacy(matches: &ArgMatches) {
+    let path = matches.value_of("from").unwrap();
+    let data = std::fs::read(path).expect("unable to read from file");
+    let legacy = open_legacy(&data, matches.value_of("key").unwrap()).unwrap_or_else(|e| {
+        eprintln!("{}: wrong passphrase, or not a profile encrypted before the sealed format ({})", path, e);
+        std::process::exit(1);
+    });
+    let public: ProfileDocument = read_document(matches.value_of("public").unwrap());
+    let mut source = serde_json::to_string_pretty(&legacy_source(&legacy, &public.data)).unwrap();
+    source.push('\n');
+    std::fs::write(matches.value_of("to").unwrap(), source).expect("unable to write to file");
+}
+
+fn seal(matches: &ArgMatches) {
+    let path = matches.value_of("source").unwrap();
+    let data = std::fs::read(path).expect("unable to read source file");
+    let mut data: Value = serde_json::from_slice(&data).unwrap_or_else(|e| {
+        eprintln!("{}: invalid JSON: {}", path, e);
+        std::process::exit(1);
+    });
+    let passphrase = new_passphrase(matches.value_of("key").unwrap());
+    let key = random_key();
+    let sealed = seal_secret_objects(&mut data, "", &mut |value, path| SealedValue {
+        key: 0,
+        data: seal_field(&serde_json::to_vec(value).unwrap(), path, &key, random_nonce()),
+    }).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        std::process::exit(1);
+    });
+    if sealed == 0 {
+        eprintln!("warning: nothing in {} is marked \"visibility\": \"secret\"", path);
+    }
+    let mut keyring = Keyring::default();
+    add_recipient_slot(&mut keyring, matches.value_of("label").unwrap(), &passphrase, kdf_params(matches), Validity::default(), &[(0, key)]);
+    write_document(matches.value_of("to").unwrap(), &Protected { format_version: FORMAT_VERSION, keyring: Some(keyring.encode()), data });
+}
+
+fn seal_asset(matches: &ArgMatches) {
+    let path = matches.value_of("from").unwrap();
+    let mime = matches.value_of("mime").or_else(|| guess_mime(path)).unwrap_or_else(|| {
+        eprintln!("{}: unknown file type, give it with --mime", path);
+        std::process::exit(1);
+    });
+    if mime.len() > u8::MAX as usize {
+        eprintln!("MIME type is too long");
+        std::process::exit(1);
+    }
+    let content = std::fs::read(path).expect("unable to read from file");
+    let passphrase = new_passphrase(matches.value_of("key").unwrap());
+    let key = random_key();
+    let mut keyring = Keyring::default();
+    add_recipient_slot(&mut keyring, matches.value_of("label").unwrap(), &passphrase, kdf_params(matches), Validity::default(), &[(ASSET_KEY_ID, key.clone())]);
+    let asset = SealedAsset::seal(mime, &content, &key, keyring, random_nonce());
+    std::fs::write(matches.value_of("to").unwrap(), asset.to_bytes()).expect("unable to write to file");
+}
+
+fn decrypt(matches: &ArgMatches) {
+    let plaintext = match read_data_file(matches.value_of("from").unwrap()) {
+        DataFile::Document(document) => {
+            let keys = recipient_keys_or_exit(&read_keyring(&document), matches.value_of("key").unwrap());
+            let mut data = AnyData(document.data);
+            reveal_or_exit(&mut data, &keys);
+            serde_json::to_vec(&data.0).unwrap()
+        }
+        DataFile::Asset(asset) => open_asset_or_exit(&asset, matches.value_of("key").unwrap()),
+    };
+    if let Some(path) = matches.value_of("to") {
+        std::fs::write(path, plaintext).expect("unable to write to file");
+    } else {
+        std::io::stdout().write_all(&plaintext).expect("unable to write to stdout");
+    }
+}
+
+fn verify(matches: &ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+    let document = match read_data_file(path) {
+        DataFile::Document(document) => document,
+        DataFile::Asset(asset) => {
+            let keys = recipient_keys_or_exit(&asset.keyring, matches.value_of("key").unwrap());
+            let content = asset.open_with(&keys).unwrap_or_else(|e| {
+                eprintln!("unable to decrypt: {}", e);
+                std::process::exit(1);
+            });
+            println!("{}: ok as \"{}\" ({}, {} bytes, {})", path, keys.label, asset.mime, content.len(), describe_validity(&keys.validity));
+            return;
+        }
+    };
+    let data_type = matches.value_of("type").or_else(|| data_type(path)).unwrap_or_else(|| {
+        eprintln!("{}: unknown kind of data, give it with --type", path);
+        std::process::exit(1);
+    });
+    let keys = recipient_keys_or_exit(&read_keyring(&document), matches.value_of("key").unwrap());
+    let mut sealed = 0;
+    let _ = AnyData(document.data.clone()).reveal(&mut |_, _| {
+        sealed += 1;
+        Ok::<_, ()>(None)
+    });
+    let mut data = AnyData(document.data);
+    let revealed = reveal_or_exit(&mut data, &keys);
+    if let Err(e) = check_data_type(data_type, data.0) {
+        eprintln!("{}: decrypted, but it is not valid {} data: {}", path, data_type, e);
+        std::process::exit(1);
+    }
+    println!("{}: ok as \"{}\" ({}/{} sealed parts, {})", path, keys.label, revealed, sealed, describe_validity(&keys.validity));
+}
+
+fn rekey(matches: &ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+    let mut file = read_data_file(path);
+    let mut keyring = file.keyring();
+    let RecipientKeys { label, validity, keys } = recipient_keys_or_exit(&keyring, matches.value_of("key").unwrap());
+    let passphrase = new_passphrase(matches.value_of("new_key").unwrap());
+    add_recipient_slot(&mut keyring, &label, &passphrase, kdf_params(matches), validity, &keys);
+    file.set_keyring(keyring);
+    file.write(matches.value_of("to").unwrap_or(path));
+}
+
+fn add_recipient(matches: &ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+    let mut file = read_data_file(path);
+    let mut keyring = file.keyring();
+    let validity = Validity {
+        not_before: matches.value_of("not_before").map(|t| parse_time_or_exit(t, false)),
+        not_after: matches.value_of("not_after").map(|t| parse_time_or_exit(t, true)),
+    };
+    if let (Some(not_before), Some(not_after)) = (validity.not_before, validity.not_after) {
+        if not_after < not_before {
+            eprintln!("--not-after is before --not-before");
+            std::process::exit(1);
+        }
+    }
+    let RecipientKeys { keys, .. } = recipient_keys_or_exit(&keyring, matches.value_of("key").unwrap());
+    let keys = if let Some(categories) = matches.values_of("categories") {
+        let document = match &file {
+            DataFile::Document(document) => document,
+            DataFile::Asset(_) => {
+                eprintln!("{}: --categories needs a profile, not a sealed asset", path);
+                std::process::exit(1);
+            }
+        };
+        let profile: Vec<ProfileData> = serde_json::from_value(document.data.clone()).unwrap_or_else(|e| {
+            eprintln!("{}: --categories needs a profile: {}", path, e);
+            std::process::exit(1);
+        });
+        let mut ids = Vec::new();
+        for name in categories {
+            let category = profile.iter().find(|c| c.category_name == name).unwrap_or_else(|| {
+                eprintln!("category \"{}\" does not exist", name);
+                std::process::exit(1);
+            });
+            for sealed in category.values.iter().filter_map(|v| v.sealed.as_ref()) {
+                if !keys.iter().any(|(id, _)| *id == sealed.key) {
+                    eprintln!("category \"{}\" cannot be opened with <key>", name);
+                    std::process::exit(1);
+                }
+                if !ids.contains(&sealed.key) {
+                    ids.push(sealed.key);
+                }
+            }
+        }
+        keys.into_iter().filter(|(id, _)| ids.contains(id)).collect()
+    } else {
+        keys
+    };
+    let passphrase = new_passphrase(matches.value_of("new_key").unwrap());
+    add_recipient_slot(&mut keyring, matches.value_of("label").unwrap(), &passphrase, kdf_params(matches), validity, &keys);
+    file.set_keyring(keyring);
+    file.write(path);
+    if let Some(site) = matches.value_of("link") {
+        println!("{}", unlock_link(site, &passphrase, matches.is_present("path-routing")));
+    }
+}
+
+fn remove_recipient(matches: &ArgMatches) {
+    let path = matches.value_of("file").unwrap();
+    let label = matches.value_of("label").unwrap();
+    let mut file = read_data_file(path);
+    let mut keyring = file.keyring();
+    if !keyring.remove_recipient(label) {
+        eprintln!("recipient \"{}\" does not exist", label);
+        std::process::exit(1);
+    }
+    file.set_keyring(keyring);
+    file.write(path);
+}
+
+fn recipients(matches: &ArgMatches) {
+    for recipient in read_data_file(matches.value_of("file").unwrap()).keyring().recipients() {
+        println!("{} (iterations: {})", recipient.label, recipient.params.iterations);
+    }
+}
+
+fn keygen(matches: &ArgMatches) {
+    let path = matches.value_of("to").unwrap();
+    if std::path::Path::new(path).exists() && !matches.is_present("force") {
+        eprintln!("{} already exists, the files signed with it would no longer verify (use --force to replace it)", path);
+        std::process::exit(1);
+    }
+    let mut seed = [0; 32];
+    rand::thread_rng().fill_bytes(&mut seed);
+    let key = SigningKey::from_seed(seed);
+    std::fs::write(path, key.encode() + "\n").expect("unable to write to file");
+    println!("{}", key.verifying_key().encode());
+}
+
+fn sign(matches: &ArgMatches) {
+    let secret = std::fs::read_to_string(matches.value_of("secret").unwrap()).expect("unable to read secret key");
+    let key = SigningKey::decode(&secret).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    for path in matches.values_of("files").unwrap() {
+        let data = std::fs::read(path).expect("unable to read file");
+        std::fs::write(signature_path(path), key.sign(&data) + "\n").expect("unable to write to file");
+    }
+}
+
+fn verify_signature(matches: &ArgMatches) {
+    let key = VerifyingKey::decode(matches.value_of("public_key").unwrap()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    let mut failed = false;
+    for path in matches.values_of("files").unwrap() {
+        let data = std::fs::read(path).expect("unable to read file");
+        let result = std::fs::read_to_string(signature_path(path))
+            .map_err(|e| e.to_string())
+            .and_then(|signature| key.verify(&data, &signature).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => println!("{}: ok", path),
+            Err(e) => {
+                println!("{}: {}", path, e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// One of [`DATA_TYPES`] by the name of `path`, such as `static/profile.data.json`
+fn data_type(path: &str) -> Option<&'static str> {
+    let name = std::path::Path::new(path).file_name()?.to_str()?;
+    DATA_TYPES.iter().copied().find(|data_type| name.starts_with(&format!("{}.", data_type)))
+}
+
+/// Check that revealed `data` parses as the model type of `data_type`.
+fn check_data_type(data_type: &str, data: Value) -> serde_json::Result<()> {
+    match data_type {
+        "profile" => serde_json::from_value::<Vec<ProfileData>>(data).map(drop),
+        "qualification" => serde_json::from_value::<Vec<QualificationData>>(data).map(drop),
+        "link" => serde_json::from_value::<Vec<MaybeSealed<LinkData>>>(data).map(drop),
+        _ => unreachable!("unknown data type {}", data_type),
+    }
+}
+
+fn signature_path(path: &str) -> String {
+    format!("{}.sig", path)
+}
+
+fn parse_profile(data: &[u8]) -> serde_json::Result<Vec<ProfileData>> {
+    serde_json::from_slice(data)
+}
+
+fn new_passphrase(input: &str) -> Passphrase {
+    let passphrase = normalize_new_passphrase(input).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    if passphrase.as_str() != input {
+        eprintln!("passphrase is normalized to \"{}\"", passphrase.as_str());
+    }
+    warn_weak_passphrase(passphrase.as_str());
+    passphrase
+}
+
+/// Normalize a passphrase a new recipient slot is made for, refusing one without any kana,
+/// whose slot every input without kana would open.
+fn normalize_new_passphrase(input: &str) -> Result<Passphrase, String> {
+    let passphrase = Passphrase::new(input);
+    if passphrase.as_str().is_empty() {
+        return Err(format!("passphrase \"{}\" normalizes to an empty passphrase, only kana and ー are kept", input));
+    }
+    Ok(passphrase)
+}
+
+fn kdf_params(matches: &ArgMatches) -> KdfParams {
+    let mut salt = [0; portfolio_crypto::SALT_SIZE];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let mut params = KdfParams::new(salt);
+    if let Some(iterations) = matches.value_of("iterations") {
+        params.iterations = iterations.parse().expect("iterations must be a positive integer");
+        assert!(params.iterations > 0, "iterations must be a positive integer");
+    }
+    params
+}
+
+fn random_nonce() -> [u8; portfolio_crypto::NONCE_SIZE] {
+    let mut nonce = [0; portfolio_crypto::NONCE_SIZE];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    nonce
+}
+
+fn random_key() -> Key {
+    let mut key = [0; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    Key::from(key)
+}
+
+/// Seal the secret values of every category with a data key of its own and give `label` access to all of them.
+fn seal_profile(source: &[ProfileSourceData], label: &str, passphrase: &Passphrase, params: KdfParams) -> ProfileDocument {
+    let keys = source.iter().enumerate().map(|(id, _)| (id, random_key())).collect::<Vec<_>>();
+    let data = public_profile(source, |id, path, value| SealedValue {
+        key: id,
+        data: seal_field(&serde_json::to_vec(value).unwrap(), path, &keys[id].1, random_nonce()),
+    });
+    let mut keyring = Keyring::default();
+    add_recipient_slot(&mut keyring, label, passphrase, params, Validity::default(), &keys);
+    Protected { format_version: FORMAT_VERSION, keyring: Some(keyring.encode()), data }
+}
+
+fn add_recipient_slot(keyring: &mut Keyring, label: &str, passphrase: &Passphrase, params: KdfParams, validity: Validity, keys: &[(usize, Key)]) {
+    let key = passphrase.derive_key(&params);
+    keyring.add_recipient(label, &key, params, validity, keys, random_nonce());
+}
+
+fn parse_time_or_exit(input: &str, end_of_day: bool) -> u64 {
+    parse_time(input, end_of_day).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// `site` with the route the frontend reads the passphrase from, `#profile?k=...` or `/profile#k=...`.
+/// The passphrase is in the fragment either way, which the browser does not send to the server.
+fn unlock_link(site: &str, passphrase: &Passphrase, path_routing: bool) -> String {
+    let encoded: String = passphrase.as_str().bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+    let route = if path_routing { "profile#" } else { "#profile?" };
+    format!("{}/{}k={}", site.trim_end_matches('/'), route, encoded)
+}
+
+/// Describe `validity` and whether it holds now
+fn describe_validity(validity: &Validity) -> String {
+    if validity.is_unlimited() {
+        return "no expiry".to_string();
+    }
+    let from = validity.not_before.map(format_time).unwrap_or_default();
+    let to = validity.not_after.map(format_time).unwrap_or_default();
+    let state = if validity.contains(now()) { "" } else { ", not valid now" };
+    format!("valid {} ~ {}{}", from, to, state)
+}
+
+/// JSON data file or sealed asset, for the commands that only touch the keyring
+enum DataFile {
+    Document(Protected<Value>),
+    Asset(SealedAsset),
+}
+
+impl DataFile {
+    fn keyring(&self) -> Keyring {
+        match self {
+            DataFile::Document(document) => read_keyring(document),
+            DataFile::Asset(asset) => asset.keyring.clone(),
+        }
+    }
+
+    fn set_keyring(&mut self, keyring: Keyring) {
+        match self {
+            DataFile::Document(document) => document.keyring = Some(keyring.encode()),
+            DataFile::Asset(asset) => asset.keyring = keyring,
+        }
+    }
+
+    fn write(&self, path: &str) {
+        match self {
+            DataFile::Document(document) => write_document(path, document),
+            DataFile::Asset(asset) => std::fs::write(path, asset.to_bytes()).expect("unable to write to file"),
+        }
+    }
+}
+
+fn read_data_file(path: &str) -> DataFile {
+    let data = std::fs::read(path).expect("unable to read file");
+    if !data.starts_with(ASSET_MAGIC) {
+        return DataFile::Document(read_document(path));
+    }
+    DataFile::Asset(SealedAsset::parse(&data).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        std::process::exit(1);
+    }))
+}
+
+fn open_asset_or_exit(asset: &SealedAsset, key: &str) -> Vec<u8> {
+    asset.open(&Passphrase::new(key)).unwrap_or_else(|e| {
+        eprintln!("unable to decrypt: {}", e);
+        std::process::exit(1);
+    })
+}
+
+fn guess_mime(path: &str) -> Option<&'static str> {
+    let extension = std::path::Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        _ => return None,
+    })
+}
+
+fn read_document<T: DeserializeOwned>(path: &str) -> Protected<T> {
+    let data = std::fs::read(path).expect("unable to read file");
+    serde_json::from_slice(&data).unwrap_or_else(|e| {
+        eprintln!("{}: invalid data file: {}", path, e);
+        std::process::exit(1);
+    })
+}
+
+fn write_document<T: Serialize>(path: &str, document: &Protected<T>) {
+    let mut data = serde_json::to_string_pretty(document).unwrap();
+    data.push('\n');
+    std::fs::write(path, data).expect("unable to write to file");
+}
+
+fn read_keyring<T>(document: &Protected<T>) -> Keyring {
+    let keyring = document.keyring.as_deref().unwrap_or_else(|| {
+        eprintln!("file has no keyring, nothing is sealed");
+        std::process::exit(1);
+    });
+    Keyring::decode(keyring).unwrap_or_else(|e| {
+        eprintln!("keyring: {}", e);
+        std::process::exit(1);
+    })
+}
+
+fn recipient_keys_or_exit(keyring: &Keyring, key: &str) -> RecipientKeys {
+    keyring.recipient_keys(&Passphrase::new(key)).unwrap_or_else(|e| {
+        eprintln!("unable to decrypt: {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Replace every sealed part `keys` can open by its plaintext, and count them.
+fn reveal_or_exit(data: &mut impl Reveal, keys: &RecipientKeys) -> usize {
+    data.reveal(&mut |sealed, path| keys.get(sealed.key).map(|key| open_field(&sealed.data, path, key)).transpose())
+        .unwrap_or_else(|e| {
+            match e {
+                RevealError::Open(e) => eprintln!("unable to decrypt: {}", e),
+                RevealError::Invalid(e) => eprintln!("decrypted, but it is not valid data: {}", e),
+            }
+            std::process::exit(1);
+        })
+}
+
+/// Rough brute-force cost of a normalized passphrase: its length times log2 of [`PASSPHRASE_ALPHABET`].
+fn estimate_bits(passphrase: &str) -> f64 {
+    passphrase.chars().count() as f64 * (PASSPHRASE_ALPHABET as f64).log2()
+}
+
+fn warn_weak_passphrase(passphrase: &str) {
+    let bits = estimate_bits(passphrase);
+    if bits < WEAK_PASSPHRASE_BITS {
+        eprintln!("warning: passphrase is weak (about {:.0} bits, recommended {:.0} bits or more)", bits, WEAK_PASSPHRASE_BITS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use portfolio_crypto::Passphrase;
+
+    use serde_json::json;
+
+    use super::{WEAK_PASSPHRASE_BITS, check_data_type, data_type, estimate_bits, normalize_new_passphrase, unlock_link};
+
+    fn bits(input: &str) -> f64 {
+        estimate_bits(Passphrase::new(input).as_str())
+    }
+
+    #[test]
+    fn estimate_bits_of_normalized_kana() {
+        assert_eq!(bits(""), 0.0);
+        assert_eq!(bits("やまだ"), 3.0 * 87f64.log2());
+        assert_eq!(bits("ヤマダ　タロウ"), 6.0 * 87f64.log2());
+        assert_eq!(bits("ｺｰﾋｰ"), 4.0 * 87f64.log2());
+        assert_eq!(bits("やまた\u{3099}"), bits("やまだ"));
+    }
+
+    #[test]
+    fn data_type_by_file_name() {
+        assert_eq!(data_type("static/profile.data.json"), Some("profile"));
+        assert_eq!(data_type("qualification.data.json"), Some("qualification"));
+        assert_eq!(data_type("link.data.json"), Some("link"));
+        assert_eq!(data_type("static/works.data.json"), None);
+        assert_eq!(data_type("profiles.json"), None);
+    }
+
+    #[test]
+    fn revealed_data_is_checked_against_the_model() {
+        let profile = json!([{ "category_name": "Profile", "values": [{ "key": "Name", "value": "Taro" }] }]);
+        assert!(check_data_type("profile", profile.clone()).is_ok());
+        assert!(check_data_type("link", profile).is_err());
+        let profile = json!([{ "category_name": "Profile", "values": [{ "key": "Name", "value": { "name": "Taro" } }] }]);
+        assert!(check_data_type("profile", profile).is_err());
+    }
+
+    #[test]
+    fn passphrase_without_kana_is_refused() {
+        for input in ["", "abc", "Taro Yamada", "1234", "山田太郎", "　!"] {
+            assert!(normalize_new_passphrase(input).is_err(), "input: {:?}", input);
+        }
+        assert_eq!(normalize_new_passphrase("Yamada やまだ").unwrap().as_str(), "やまだ");
+    }
+
+    #[test]
+    fn link_key_is_in_the_fragment() {
+        let passphrase = Passphrase::new("やまだ");
+        let encoded = "%E3%82%84%E3%81%BE%E3%81%A0";
+        assert_eq!(unlock_link("https://example.com/", &passphrase, false), format!("https://example.com/#profile?k={}", encoded));
+        assert_eq!(unlock_link("https://example.com", &passphrase, true), format!("https://example.com/profile#k={}", encoded));
+    }
+
+    #[test]
+    fn short_name_is_weak() {
+        assert!(bits("やまだたろう") < WEAK_PASSPHRASE_BITS);
+        assert!(bits("じゅげむじゅげむごこうのすりきれ") > WEAK_PASSPHRASE_BITS);
+    }
 }
@@ -0,0 +1,201 @@
+use portfolio_model::profile::{ProfileData, ProfileValueData, Secret, WrappedString};
+use portfolio_model::protected::{SealedValue, child_path};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Visibility {
+    Public,
+    Secret,
+}
+
+/// One value of the annotated source. `visibility` is required so that nothing is published by accident.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ProfileSourceValue {
+    key: String,
+    value: WrappedString,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    visibility: Visibility,
+    /// published in place of a secret value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ProfileSourceData {
+    category_name: String,
+    values: Vec<ProfileSourceValue>,
+}
+
+/// Treat every value of a plain profile as secret.
+pub(crate) fn secret_source(profile: &[ProfileData]) -> Vec<ProfileSourceData> {
+    profile.iter()
+        .map(|category| ProfileSourceData {
+            category_name: category.category_name.clone(),
+            values: category.values.iter()
+                .map(|v| ProfileSourceValue { key: v.key.clone(), value: v.value.clone(), status: v.status.clone(), visibility: Visibility::Secret, hint: None })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Source of a profile encrypted before the sealed format, `legacy` holding every real value.
+/// A value with a [`Secret`] placeholder in the published `public` profile stays secret with its hint,
+/// one shown there as is stays public, and one missing from it is secret.
+pub(crate) fn legacy_source(legacy: &[ProfileData], public: &[ProfileData]) -> Vec<ProfileSourceData> {
+    legacy.iter()
+        .map(|category| ProfileSourceData {
+            category_name: category.category_name.clone(),
+            values: category.values.iter()
+                .map(|v| {
+                    let published = public.iter()
+                        .filter(|c| c.category_name == category.category_name)
+                        .flat_map(|c| &c.values)
+                        .find(|p| p.key == v.key);
+                    let (visibility, hint) = match published.map(|p| &p.value) {
+                        Some(WrappedString::Secret { secret }) => (Visibility::Secret, secret.hint.clone()),
+                        Some(_) => (Visibility::Public, None),
+                        None => (Visibility::Secret, None),
+                    };
+                    ProfileSourceValue { key: v.key.clone(), value: v.value.clone(), status: v.status.clone(), visibility, hint }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Profile published as `profile.data.json`. Every secret value is replaced by a [`Secret`] placeholder
+/// and carries its ciphertext, made by `seal` from the index of its category, its place and the real value.
+pub(crate) fn public_profile(source: &[ProfileSourceData], mut seal: impl FnMut(usize, &str, &WrappedString) -> SealedValue) -> Vec<ProfileData> {
+    source.iter().enumerate()
+        .map(|(index, category)| ProfileData {
+            category_name: category.category_name.clone(),
+            values: category.values.iter().enumerate()
+                .map(|(value_index, v)| {
+                    let (value, sealed) = match v.visibility {
+                        Visibility::Public => (v.value.clone(), None),
+                        Visibility::Secret => {
+                            let path = child_path(&child_path(&child_path("", index), "values"), value_index);
+                            (WrappedString::Secret { secret: Secret { hint: v.hint.clone() } }, Some(seal(index, &path, &v.value)))
+                        }
+                    };
+                    ProfileValueData { key: v.key.clone(), value, status: v.status.clone(), sealed }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Key of the first public value with a hint, which would never be shown.
+pub(crate) fn public_value_with_hint(source: &[ProfileSourceData]) -> Option<&str> {
+    source.iter()
+        .flat_map(|category| &category.values)
+        .find(|v| v.visibility == Visibility::Public && v.hint.is_some())
+        .map(|v| v.key.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use portfolio_crypto::{KdfParams, Keyring, OpenError, Passphrase, open_field};
+    use portfolio_model::profile::{ProfileData, ProfileDocument, WrappedString};
+    use portfolio_model::protected::{Reveal, RevealError};
+
+    use super::{ProfileSourceData, legacy_source};
+    use crate::seal_profile;
+
+    const SOURCE: &str = r#"[
+        {
+            "category_name": "Profile",
+            "values": [
+                { "key": "Name", "value": "Taro", "visibility": "public" },
+                { "key": "Birthday", "value": "2000-01-01", "visibility": "secret", "hint": "date" }
+            ]
+        },
+        {
+            "category_name": "Career",
+            "values": [
+                { "key": "2018", "value": "Entered", "status": "now", "visibility": "secret" },
+                { "key": "2015", "value": "Graduated", "visibility": "public" },
+                { "key": "2014", "value": [{ "value": "Club", "ruby": "くらぶ" }], "visibility": "secret" }
+            ]
+        }
+    ]"#;
+
+    fn sealed(passphrase: &Passphrase) -> (Vec<ProfileSourceData>, ProfileDocument) {
+        let source: Vec<ProfileSourceData> = serde_json::from_str(SOURCE).unwrap();
+        let document = seal_profile(&source, "owner", passphrase, KdfParams { salt: [1; portfolio_crypto::SALT_SIZE], iterations: 1 });
+        (source, document)
+    }
+
+    fn reveal(document: &ProfileDocument, passphrase: &Passphrase) -> Result<(usize, Vec<ProfileData>), RevealError<OpenError>> {
+        let recipient = Keyring::decode(document.keyring.as_deref().unwrap()).unwrap().recipient_keys(passphrase).unwrap();
+        let mut revealed = document.data.clone();
+        let count = revealed.reveal(&mut |sealed, path| recipient.get(sealed.key).map(|key| open_field(&sealed.data, path, key)).transpose())?;
+        Ok((count, revealed))
+    }
+
+    fn keys(profile: &[ProfileData]) -> Vec<(String, Vec<String>)> {
+        profile.iter()
+            .map(|category| (category.category_name.clone(), category.values.iter().map(|v| v.key.clone()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn public_and_sealed_profile_have_the_same_categories_and_keys() {
+        let passphrase = Passphrase::new("やまだ");
+        let (source, document) = sealed(&passphrase);
+        let expected: Vec<(String, Vec<String>)> = source.iter()
+            .map(|category| (category.category_name.clone(), category.values.iter().map(|v| v.key.clone()).collect()))
+            .collect();
+        assert_eq!(keys(&document.data), expected);
+
+        let (count, revealed) = reveal(&document, &passphrase).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(keys(&revealed), expected);
+        for (revealed, source) in revealed.iter().flat_map(|c| &c.values).zip(source.iter().flat_map(|c| &c.values)) {
+            assert_eq!(revealed.value, source.value);
+            assert_eq!(revealed.status, source.status);
+            assert!(revealed.sealed.is_none());
+        }
+        // only the secret values are replaced in the public profile
+        let public: Vec<bool> = document.data.iter().flat_map(|c| &c.values).map(|v| matches!(v.value, WrappedString::Secret { .. })).collect();
+        assert_eq!(public, [false, true, true, false, true]);
+    }
+
+    #[test]
+    fn legacy_profile_keeps_what_was_published() {
+        let legacy: Vec<ProfileData> = serde_json::from_str(r#"[
+            { "category_name": "Profile", "values": [{ "key": "Name", "value": "Taro" }, { "key": "Birthday", "value": "2000-01-01" }] },
+            { "category_name": "Career", "values": [{ "key": "2015", "value": "Graduated", "status": "done" }] }
+        ]"#).unwrap();
+        let public: Vec<ProfileData> = serde_json::from_str(r#"[
+            { "category_name": "Profile", "values": [{ "key": "Name", "value": { "secret": { "hint": "name" } } }, { "key": "Birthday", "value": "2000-01-01" }] }
+        ]"#).unwrap();
+        let source = serde_json::to_value(legacy_source(&legacy, &public)).unwrap();
+        assert_eq!(source, serde_json::json!([
+            { "category_name": "Profile", "values": [
+                { "key": "Name", "value": "Taro", "visibility": "secret", "hint": "name" },
+                { "key": "Birthday", "value": "2000-01-01", "visibility": "public" },
+            ] },
+            { "category_name": "Career", "values": [{ "key": "2015", "value": "Graduated", "status": "done", "visibility": "secret" }] },
+        ]));
+    }
+
+    #[test]
+    fn sealed_values_only_open_in_their_place() {
+        let passphrase = Passphrase::new("やまだ");
+        let (_, mut document) = sealed(&passphrase);
+        // two values of the same category, sealed with the same data key
+        let values = &mut document.data[1].values;
+        let first = values[0].sealed.take();
+        values[0].sealed = values[2].sealed.clone();
+        assert!(matches!(reveal(&document, &passphrase), Err(RevealError::Open(OpenError::Corrupted))));
+        document.data[1].values[0].sealed = first;
+        assert!(reveal(&document, &passphrase).is_ok());
+        document.data[1].values.swap(0, 2);
+        assert!(matches!(reveal(&document, &passphrase), Err(RevealError::Open(OpenError::Corrupted))));
+    }
+}
@@ -0,0 +1,67 @@
+//! Sealing and revealing data files whose types the encrypter does not know.
+
+use portfolio_model::protected::{Reveal, RevealError, SealedValue, child_path};
+use serde_json::Value;
+
+/// Replace every object with `"visibility": "secret"` by `{"sealed": ...}`, made by `seal` from the object
+/// without its visibility and its place under `path`, and drop `"visibility": "public"`. Returns the number of sealed objects.
+pub(crate) fn seal_secret_objects(value: &mut Value, path: &str, seal: &mut impl FnMut(&Value, &str) -> SealedValue) -> Result<usize, String> {
+    match value {
+        Value::Array(items) => items.iter_mut().enumerate()
+            .try_fold(0, |count, (index, item)| Ok(count + seal_secret_objects(item, &child_path(path, index), seal)?)),
+        Value::Object(object) => {
+            let visibility = object.remove("visibility");
+            let mut count = object.iter_mut()
+                .try_fold(0, |count, (key, item)| Ok::<_, String>(count + seal_secret_objects(item, &child_path(path, key), seal)?))?;
+            match visibility.as_ref().map(|v| v.as_str()) {
+                None | Some(Some("public")) => {}
+                Some(Some("secret")) => {
+                    let sealed = serde_json::to_value(seal(value, path)).unwrap();
+                    *value = serde_json::json!({ "sealed": sealed });
+                    count += 1;
+                }
+                Some(_) => return Err(format!("visibility must be \"public\" or \"secret\", found {}", visibility.unwrap())),
+            }
+            Ok(count)
+        }
+        _ => Ok(0),
+    }
+}
+
+/// Any JSON document. An object that is only `{"sealed": ...}` is replaced by its plaintext,
+/// one with other fields too (a profile value) gets the plaintext as its `value`.
+pub(crate) struct AnyData(pub(crate) Value);
+
+impl Reveal for AnyData {
+    fn reveal_at<E>(&mut self, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+        reveal_value(&mut self.0, path, open)
+    }
+}
+
+fn reveal_value<E>(value: &mut Value, path: &str, open: &mut impl FnMut(&SealedValue, &str) -> Result<Option<Vec<u8>>, E>) -> Result<usize, RevealError<E>> {
+    let object = match value {
+        Value::Array(items) => return items.iter_mut().enumerate()
+            .try_fold(0, |count, (index, item)| Ok(count + reveal_value(item, &child_path(path, index), open)?)),
+        Value::Object(object) => object,
+        _ => return Ok(0),
+    };
+    let plaintext = match object.get("sealed") {
+        Some(sealed) => {
+            let sealed: SealedValue = serde_json::from_value(sealed.clone()).map_err(RevealError::Invalid)?;
+            open(&sealed, path).map_err(RevealError::Open)?
+        }
+        None => None,
+    };
+    let mut count = 0;
+    if let Some(plaintext) = plaintext {
+        let revealed: Value = serde_json::from_slice(&plaintext).map_err(RevealError::Invalid)?;
+        object.remove("sealed");
+        count += 1;
+        if object.is_empty() {
+            *value = revealed;
+            return Ok(count + reveal_value(value, path, open)?);
+        }
+        object.insert("value".to_string(), revealed);
+    }
+    object.iter_mut().try_fold(count, |count, (key, item)| Ok(count + reveal_value(item, &child_path(path, key), open)?))
+}
@@ -0,0 +1,114 @@
+//! Unix time for recipient validity, without pulling in a calendar crate.
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Parse unix seconds, or a UTC date `YYYY-MM-DD` taken as its first second, or its last with `end_of_day`.
+pub(crate) fn parse_time(input: &str, end_of_day: bool) -> Result<u64, String> {
+    if let Ok(seconds) = input.parse() {
+        return Ok(seconds);
+    }
+    let invalid = || format!("\"{}\" is neither unix seconds nor a date YYYY-MM-DD", input);
+    let parts = input.split('-').map(|part| part.parse::<u32>().map_err(|_| invalid())).collect::<Result<Vec<_>, _>>()?;
+    let (year, month, day) = match parts.as_slice() {
+        &[year, month, day] if year >= 1970 && (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day) => (year, month, day),
+        _ => return Err(invalid()),
+    };
+    let start = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+    Ok(if end_of_day { start + SECONDS_PER_DAY - 1 } else { start })
+}
+
+/// Format unix seconds as `YYYY-MM-DD hh:mm:ss UTC`.
+pub(crate) fn format_time(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days(seconds / SECONDS_PER_DAY);
+    let time = seconds % SECONDS_PER_DAY;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+pub(crate) fn now() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).expect("clock is before 1970").as_secs()
+}
+
+fn is_leap_year(year: u32) -> bool {
+    match (year % 400, year % 100, year % 4) {
+        (0, _, _) => true,
+        (_, 0, _) => false,
+        (_, _, 0) => true,
+        _ => false,
+    }
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn days_from_civil(year: u32, month: u32, day: u32) -> u64 {
+    let days_before_year = (1970..year).map(|y| if is_leap_year(y) { 366 } else { 365 }).sum::<u64>();
+    let days_before_month = (1..month).map(|m| days_in_month(year, m) as u64).sum::<u64>();
+    days_before_year + days_before_month + day as u64 - 1
+}
+
+fn civil_from_days(mut days: u64) -> (u32, u32, u32) {
+    let mut year = 1970;
+    loop {
+        let length = if is_leap_year(year) { 366 } else { 365 };
+        if days < length {
+            break;
+        }
+        days -= length;
+        year += 1;
+    }
+    let mut month = 1;
+    while days >= days_in_month(year, month) as u64 {
+        days -= days_in_month(year, month) as u64;
+        month += 1;
+    }
+    (year, month, days as u32 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{days_in_month, format_time, is_leap_year, parse_time};
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2024));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2023));
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+        assert_eq!(days_in_month(2023, 4), 30);
+        assert_eq!(days_in_month(2023, 12), 31);
+    }
+
+    #[test]
+    fn parse_dates() {
+        assert_eq!(parse_time("1970-01-01", false), Ok(0));
+        assert_eq!(parse_time("1970-01-01", true), Ok(86_399));
+        assert_eq!(parse_time("2000-03-01", false), Ok(951_868_800));
+        assert_eq!(parse_time("2024-02-29", false), Ok(1_709_164_800));
+        assert_eq!(parse_time("2024-12-31", true), Ok(1_735_689_599));
+        assert_eq!(parse_time("1700000000", true), Ok(1_700_000_000));
+    }
+
+    #[test]
+    fn reject_invalid_dates() {
+        for input in ["2023-02-29", "1900-02-29", "2024-13-01", "2024-04-31", "2024-00-10", "2024-01-00", "1969-12-31", "2024-01", "yesterday"] {
+            assert!(parse_time(input, false).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn format_round_trip() {
+        assert_eq!(format_time(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_time(1_709_251_199), "2024-02-29 23:59:59 UTC");
+        for date in ["1972-02-29", "2000-02-29", "2000-12-31", "2024-03-01"] {
+            assert_eq!(format_time(parse_time(date, true).unwrap()), format!("{} 23:59:59 UTC", date));
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use clap::{App, AppSettings, Arg, SubCommand};
+
+mod pages;
+mod pwa;
+mod signatures;
+
+fn main() {
+    let dist = Arg::with_name("dist")
+        .long("dist")
+        .takes_value(true)
+        .default_value("dist")
+        .help("directory parcel built the site into");
+    let matches = App::new("site_builder")
+        .about("finish the site parcel built, with the files parcel cannot make")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("signatures")
+            .about("copy the signatures encrypter sign wrote next to the data files, under the names the site fetches them by; run it before pwa")
+            .arg(dist.clone())
+            .arg(Arg::with_name("static")
+                .long("static")
+                .takes_value(true)
+                .default_value("static")
+                .help("directory of the data files and their signatures")))
+        .subcommand(SubCommand::with_name("pwa")
+            .about("write the web manifest and the service worker precaching every built file, and link them from index.html")
+            .arg(dist.clone()))
+        .subcommand(SubCommand::with_name("pages")
+            .about("write 404.html and an index.html for each route, for a site built with the path-routing feature; run it after pwa")
+            .arg(dist.clone())
+            .arg(Arg::with_name("base")
+                .long("base")
+                .takes_value(true)
+                .default_value("/")
+                .help("path the site is served at, which the URLs in index.html are relative to"))
+            .arg(Arg::with_name("works")
+                .long("works")
+                .takes_value(true)
+                .default_value("static/works.data.json")
+                .help("works data, with a page for each node of the graph")))
+        .get_matches();
+    let result = match matches.subcommand() {
+        ("signatures", Some(matches)) => signatures::build(
+            Path::new(matches.value_of("dist").unwrap()),
+            Path::new(matches.value_of("static").unwrap()),
+        ),
+        ("pwa", Some(matches)) => pwa::build(Path::new(matches.value_of("dist").unwrap())),
+        ("pages", Some(matches)) => pages::build(
+            Path::new(matches.value_of("dist").unwrap()),
+            matches.value_of("base").unwrap(),
+            Path::new(matches.value_of("works").unwrap()),
+        ),
+        _ => unreachable!(),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
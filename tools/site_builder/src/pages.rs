@@ -0,0 +1,128 @@
+//! Pages for the routes of a site built with the `path-routing` feature, so that a static host such as GitHub Pages
+//! serves the app at each of them without rewrites: `<route>/index.html` for the known routes and `404.html` for anything else.
+//! Each is a copy of `index.html` with a `<base>`, as its URLs are relative to the root,
+//! and with the title and `og:url` of its route for link previews.
+
+use std::path::Path;
+
+use portfolio_model::works::WorksData;
+
+/// Paths of `AppRoute` in `src/routes/mod.rs` of the site, with the titles of their pages
+const ROUTES: [(&str, &str); 5] = [
+    ("profile", "Profile"),
+    ("qualification", "Qualifications"),
+    ("link", "Links"),
+    ("works", "Works"),
+    ("license", "License"),
+];
+
+pub(crate) fn build(dist: &Path, base: &str, works: &Path) -> Result<(), String> {
+    let index_path = dist.join("index.html");
+    let index = std::fs::read_to_string(&index_path).map_err(|e| format!("failed to read {}: {}", index_path.display(), e))?;
+    let index = insert_base(&index, base)?;
+
+    let mut pages: Vec<(Vec<String>, String)> = ROUTES.iter()
+        .map(|(path, title)| (vec![path.to_string()], title.to_string()))
+        .collect();
+    for (kind, name) in works_nodes(works)? {
+        // the host decodes the path into a file name, which cannot hold these
+        if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+            eprintln!("no page for the {} \"{}\", it is left to 404.html", kind, name);
+            continue;
+        }
+        pages.push((vec!["works".to_string(), kind.to_string(), name.clone()], name));
+    }
+
+    for (segments, title) in &pages {
+        let dir = segments.iter().fold(dist.to_path_buf(), |dir, segment| dir.join(segment));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+        let path = segments.iter().map(|segment| encode_segment(segment)).collect::<Vec<_>>().join("/");
+        write(&dir.join("index.html"), &page(&index, &path, title))?;
+    }
+    write(&dist.join("404.html"), &index)?;
+    println!("{}: {} pages and 404.html written", dist.display(), pages.len());
+    Ok(())
+}
+
+/// Kind and name of every node of the works graph, none if the data has not been generated
+fn works_nodes(path: &Path) -> Result<Vec<(&'static str, String)>, String> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("no pages for the works graph, failed to read {}: {}", path.display(), e);
+            return Ok(Vec::new());
+        }
+    };
+    let works: WorksData = serde_json::from_slice(&content).map_err(|e| format!("{} is malformed: {}", path.display(), e))?;
+    let repositories = works.repositories.into_iter().map(|r| ("repository", r.name));
+    let languages = works.languages.into_iter().map(|l| ("language", l.name));
+    let technologies = works.technologies.into_iter().map(|t| ("technology", t.name));
+    Ok(repositories.chain(languages).chain(technologies).collect())
+}
+
+/// `<base href>` first in `<head>`, before any relative URL
+fn insert_base(index: &str, base: &str) -> Result<String, String> {
+    if index.contains("<base ") {
+        return Ok(index.to_string());
+    }
+    let head = index.find("<head>").ok_or("no <head> in index.html")? + "<head>".len();
+    Ok(format!("{}<base href=\"{}\">{}", &index[..head], base, &index[head..]))
+}
+
+/// `index` with the title of the page at `path` and its URL for link previews
+fn page(index: &str, path: &str, title: &str) -> String {
+    let mut page = index.to_string();
+    if let Some(site_title) = element_text(index, "title") {
+        let full_title = format!("{} - {}", escape(title), site_title);
+        page = page.replacen(&format!("<title>{}</title>", site_title), &format!("<title>{}</title>", full_title), 1);
+        page = set_meta_content(&page, "og:title", &full_title);
+    }
+    if let Some(site_url) = meta_content(index, "og:url") {
+        page = set_meta_content(&page, "og:url", &format!("{}/{}/", site_url.trim_end_matches('/'), path));
+    }
+    page
+}
+
+fn element_text<'a>(html: &'a str, tag: &str) -> Option<&'a str> {
+    let start = html.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = html[start..].find(&format!("</{}>", tag))?;
+    Some(&html[start..start + end])
+}
+
+/// Range of the `content` attribute value of `<meta property="<property>" content="...">`
+fn meta_content_range(html: &str, property: &str) -> Option<(usize, usize)> {
+    let tag_start = html.find(&format!("property=\"{}\"", property))?;
+    let tag_end = tag_start + html[tag_start..].find('>')?;
+    let start = tag_start + html[tag_start..tag_end].find("content=\"")? + "content=\"".len();
+    let end = start + html[start..tag_end].find('"')?;
+    Some((start, end))
+}
+
+fn meta_content<'a>(html: &'a str, property: &str) -> Option<&'a str> {
+    meta_content_range(html, property).map(|(start, end)| &html[start..end])
+}
+
+fn set_meta_content(html: &str, property: &str, content: &str) -> String {
+    match meta_content_range(html, property) {
+        Some((start, end)) => format!("{}{}{}", &html[..start], content, &html[end..]),
+        None => html.to_string(),
+    }
+}
+
+/// Percent-encode all but the unreserved characters, as `encodeURIComponent` does in the site
+fn encode_segment(segment: &str) -> String {
+    segment.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'!' | b'*' | b'\'' | b'(' | b')' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
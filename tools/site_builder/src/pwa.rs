@@ -0,0 +1,129 @@
+//! Web manifest and service worker of the built site, so that it installs as an app and works offline.
+//! The service worker precaches every file in `dist/`, and gets a new cache name whenever one of them changes,
+//! which makes the browser install it again and drop the old cache.
+
+use std::path::Path;
+
+use portfolio_assets::bundled_name;
+use serde_json::{Value, json};
+
+const MANIFEST: &str = "manifest.webmanifest";
+const SERVICE_WORKER: &str = "sw.js";
+const SERVICE_WORKER_TEMPLATE: &str = include_str!("sw.js");
+/// `static/icon.png` and `static/icon.svg`, copied by parcel as `index.ts` requires them
+const ICON_PNG: &str = "icon.png";
+const ICON_SVG: &str = "icon.svg";
+const SHORT_NAME: &str = "Portfolio";
+/// `bg-light` of bootstrap, the background of the navigation bar
+const THEME_COLOR: &str = "#f8f9fa";
+
+pub(crate) fn build(dist: &Path) -> Result<(), String> {
+    let index_path = dist.join("index.html");
+    let index = read_to_string(&index_path)?;
+
+    let manifest = manifest(dist, &index)?;
+    write(&dist.join(MANIFEST), serde_json::to_string_pretty(&manifest).unwrap().as_bytes())?;
+    if !index.contains("rel=\"manifest\"") {
+        let head_end = index.find("</head>").ok_or_else(|| format!("no </head> in {}", index_path.display()))?;
+        let links = format!("<link rel=\"manifest\" href=\"{}\"><meta name=\"theme-color\" content=\"{}\">", MANIFEST, THEME_COLOR);
+        write(&index_path, format!("{}{}{}", &index[..head_end], links, &index[head_end..]).as_bytes())?;
+    }
+
+    let mut files = Vec::new();
+    collect_files(dist, dist, &mut files)?;
+    files.retain(|file| file != SERVICE_WORKER && !file.ends_with(".map"));
+    files.sort();
+    let mut version = md5::Context::new();
+    let mut precache = vec!["./".to_string()];
+    for file in &files {
+        version.consume(file.as_bytes());
+        version.consume(std::fs::read(dist.join(file)).map_err(|e| format!("failed to read {}: {}", file, e))?);
+        precache.push(format!("./{}", file));
+    }
+    // bootstrap and jquery come from their CDNs, the page is unstyled offline without them
+    precache.extend(external_urls(&index));
+
+    let service_worker = SERVICE_WORKER_TEMPLATE
+        .replace("__VERSION__", &format!("{:x}", version.compute()))
+        .replace("__PRECACHE__", &serde_json::to_string_pretty(&precache).unwrap());
+    write(&dist.join(SERVICE_WORKER), service_worker.as_bytes())?;
+    println!("{}: {} files precached", dist.join(SERVICE_WORKER).display(), precache.len());
+    Ok(())
+}
+
+fn manifest(dist: &Path, index: &str) -> Result<Value, String> {
+    let name = index.find("<title>")
+        .and_then(|start| {
+            let title = &index[start + "<title>".len()..];
+            title.find("</title>").map(|end| &title[..end])
+        })
+        .ok_or("no <title> in index.html")?;
+    let png = bundled_name(ICON_PNG);
+    let (width, height) = png_size(&dist.join(&png))?;
+    let mut icons = vec![json!({ "src": png, "sizes": format!("{}x{}", width, height), "type": "image/png", "purpose": "any" })];
+    let svg = bundled_name(ICON_SVG);
+    if dist.join(&svg).is_file() {
+        icons.push(json!({ "src": svg, "sizes": "any", "type": "image/svg+xml", "purpose": "any" }));
+    }
+    Ok(json!({
+        "name": name,
+        "short_name": SHORT_NAME,
+        "lang": "ja",
+        "start_url": "./",
+        "scope": "./",
+        "display": "standalone",
+        "background_color": "#ffffff",
+        "theme_color": THEME_COLOR,
+        "icons": icons,
+    }))
+}
+
+/// Width and height from the IHDR chunk, which comes first in every PNG file
+fn png_size(path: &Path) -> Result<(u32, u32), String> {
+    let content = std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    if content.len() < 24 || &content[..8] != b"\x89PNG\r\n\x1a\n" || &content[12..16] != b"IHDR" {
+        return Err(format!("{} is not a PNG file", path.display()));
+    }
+    let read_u32 = |offset: usize| u32::from_be_bytes([content[offset], content[offset + 1], content[offset + 2], content[offset + 3]]);
+    Ok((read_u32(16), read_u32(20)))
+}
+
+/// `src` and `href` attributes of `index.html` pointing at other sites
+fn external_urls(index: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    for attribute in &["src=\"https://", "href=\"https://"] {
+        let prefix_len = attribute.len() - "https://".len();
+        for (start, _) in index.match_indices(attribute) {
+            let url = &index[start + prefix_len..];
+            if let Some(end) = url.find('"') {
+                urls.push(url[..end].to_string());
+            }
+        }
+    }
+    urls
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| format!("failed to read {}: {}", dir.display(), e))?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap().components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        files.push(relative);
+    }
+    Ok(())
+}
+
+fn read_to_string(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))
+}
+
+fn write(path: &Path, content: &[u8]) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
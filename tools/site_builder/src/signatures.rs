@@ -0,0 +1,38 @@
+//! Detached signatures of the data files, written by `encrypter sign` next to them in `static/`.
+//! They are not committed, so `index.ts` cannot require them without breaking the build of a checkout that has none;
+//! instead each one that exists is copied into `dist/` under the name parcel would give it, the URL the site fetches.
+
+use std::path::Path;
+
+use portfolio_assets::bundled_name;
+
+const DATA_SUFFIX: &str = ".data.json";
+const SIGNATURE_SUFFIX: &str = ".sig";
+
+pub(crate) fn build(dist: &Path, static_dir: &Path) -> Result<(), String> {
+    let entries = std::fs::read_dir(static_dir).map_err(|e| format!("failed to read {}: {}", static_dir.display(), e))?;
+    let mut data_files = Vec::new();
+    for entry in entries {
+        let name = entry.map_err(|e| format!("failed to read {}: {}", static_dir.display(), e))?.file_name();
+        let name = name.to_string_lossy();
+        if name.ends_with(DATA_SUFFIX) {
+            data_files.push(name.into_owned());
+        }
+    }
+    data_files.sort();
+
+    let mut copied = 0;
+    for data_file in &data_files {
+        let signature = format!("{}{}", data_file, SIGNATURE_SUFFIX);
+        let source = static_dir.join(&signature);
+        if !source.is_file() {
+            eprintln!("{} is not signed, a site built with PORTFOLIO_SIGNING_KEY refuses it", static_dir.join(data_file).display());
+            continue;
+        }
+        let destination = dist.join(bundled_name(&signature));
+        std::fs::copy(&source, &destination).map_err(|e| format!("failed to copy {} to {}: {}", source.display(), destination.display(), e))?;
+        copied += 1;
+    }
+    println!("{}: {} of {} signatures copied", dist.display(), copied, data_files.len());
+    Ok(())
+}
@@ -0,0 +1,36 @@
+// Service worker of the site, written into dist/ by `site_builder pwa` from tools/site_builder/src/sw.js
+// with the cache name and the list of files filled in.
+
+const CACHE = "portfolio-__VERSION__";
+const PRECACHE = __PRECACHE__;
+
+self.addEventListener("install", event => {
+    event.waitUntil(caches.open(CACHE)
+        .then(cache => cache.addAll(PRECACHE))
+        .then(() => self.skipWaiting()));
+});
+
+self.addEventListener("activate", event => {
+    event.waitUntil(caches.keys()
+        .then(keys => Promise.all(keys.filter(key => key !== CACHE).map(key => caches.delete(key))))
+        .then(() => self.clients.claim()));
+});
+
+// Parcel names the files by their path, not their contents, so the network goes first and the cache is what is left offline.
+// The page revalidates the data files itself with If-None-Match, a 304 for those is passed through as it is.
+self.addEventListener("fetch", event => {
+    const request = event.request;
+    if (request.method !== "GET") {
+        return;
+    }
+    event.respondWith(fetch(request)
+        .then(response => {
+            if (response.ok) {
+                const copy = response.clone();
+                event.waitUntil(caches.open(CACHE).then(cache => cache.put(request, copy)));
+            }
+            return response;
+        })
+        .catch(() => caches.match(request, {ignoreSearch: true})
+            .then(cached => cached || (request.mode === "navigate" ? caches.match("./") : Response.error()))));
+});
@@ -3,9 +3,10 @@ use std::collections::{BTreeSet, HashMap, HashSet};
 
 use clap::Arg;
 use once_cell::sync::OnceCell;
+use portfolio_model::works::{Language, Repository, TechnologyStack, WorksData};
 use serde::Deserialize;
 
-use crate::repository_list::{get_works, Language, Repository, RepositoryLanguage, RepositoryRelatedRepositories, RepositoryTechnologyStacks, TechnologyStack, Works};
+use crate::repository_list::get_works;
 
 mod repository_list;
 
@@ -55,24 +56,24 @@ fn main() {
 
 fn write_or_print(data: &str, path: Option<&str>) {
     if let Some(path) = path {
-        if let Ok(_) = std::fs::write(path, data) {
+        if std::fs::write(path, data).is_ok() {
             return;
         }
     }
     println!("{}", data);
 }
 
-fn write_works(works: &Works, path: Option<&str>) {
+fn write_works(works: &WorksData, path: Option<&str>) {
     write_or_print(&serde_json::to_string(works).expect("failed to serialize"), path);
 }
 
-fn write_graph(works: &Works, path: Option<&str>) {
+fn write_graph(works: &WorksData, path: Option<&str>) {
     write_or_print(&to_graph(works), path);
 }
 
-fn to_graph(works: &Works) -> String {
+fn to_graph(works: &WorksData) -> String {
     let mut result = String::new();
-    let Works { repositories: repos, languages: lang, technologies: tech } = works;
+    let WorksData { repositories: repos, languages: lang, technologies: tech, .. } = works;
     result.push_str("graph{graph[rankdir=LR];node[style=\"filled\",color=\"lightgray\"];edge[color=\"lightgray\"];");
     {
         result.push_str("subgraph language{node[shape=box];");
@@ -83,8 +84,8 @@ fn to_graph(works: &Works) -> String {
         for i in 0..lang.len() {
             result.push_str(&format!("language{};", i));
         }
-        result.push_str("}");
-        result.push_str("}");
+        result.push('}');
+        result.push('}');
     }
     {
         result.push_str("subgraph repository{node[];");
@@ -95,8 +96,8 @@ fn to_graph(works: &Works) -> String {
         for i in 0..repos.len() {
             result.push_str(&format!("repository{};", i));
         }
-        result.push_str("}");
-        result.push_str("}");
+        result.push('}');
+        result.push('}');
     }
     {
         result.push_str("subgraph technology{node[shape=octagon];");
@@ -107,34 +108,23 @@ fn to_graph(works: &Works) -> String {
         for i in 0..tech.len() {
             result.push_str(&format!("technology{};", i));
         }
-        result.push_str("}");
-        result.push_str("}");
+        result.push('}');
+        result.push('}');
     }
 
     for (i, repo) in repos.iter().enumerate() {
-        match &repo.language {
-            RepositoryLanguage::IndexList(list) => {
-                for (lang, _) in list {
-                    result.push_str(&format!("repository{}--language{}[id=\"repository{0}_language{1}\"];", i, lang));
-                }
-            }
-            _ => unreachable!(),
+        for (lang, _) in &repo.language {
+            result.push_str(&format!("repository{}--language{}[id=\"repository{0}_language{1}\"];", i, lang));
         }
-        match &repo.technology_stacks {
-            RepositoryTechnologyStacks::IndexList(list) => for tech in list {
-                result.push_str(&format!("repository{}--technology{}[id=\"repository{0}_technology{1}\"];", i, tech));
-            }
-            _ => unreachable!()
+        for tech in &repo.technology_stacks {
+            result.push_str(&format!("repository{}--technology{}[id=\"repository{0}_technology{1}\"];", i, tech));
         }
-        match &repo.related_repositories {
-            RepositoryRelatedRepositories::IndexList(list) => for repo in list {
-                result.push_str(&format!("repository{}--repository{}[id=\"repository{0}_repository{1}\"];", i, repo));
-            }
-            _ => unreachable!()
+        for repo in &repo.related_repositories {
+            result.push_str(&format!("repository{}--repository{}[id=\"repository{0}_repository{1}\"];", i, repo));
         }
     }
 
-    result.push_str("}");
+    result.push('}');
     result
 }
 
@@ -170,7 +160,7 @@ fn get_additional_information(path: Option<&str>) -> RepositoryAdditionalInforma
 
 fn simplify(map: HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
     map.into_iter()
-        .map(|(k, v)|
+        .flat_map(|(k, v)|
             v.into_iter()
                 .collect::<HashSet<_>>()
                 .into_iter()
@@ -182,13 +172,12 @@ fn simplify(map: HashMap<String, Vec<String>>) -> HashMap<String, Vec<String>> {
                         Ordering::Equal => None,
                     }
                 }))
-        .flatten()
         .collect::<BTreeSet<_>>()
         .into_iter()
         .fold(HashMap::new(), |mut map, next| {
             let (key, val) = next;
             map.entry(key)
-                .or_insert_with(|| HashSet::new())
+                .or_insert_with(HashSet::new)
                 .insert(val);
             map
         })
@@ -1,7 +1,9 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 
+use portfolio_model::FORMAT_VERSION;
+use portfolio_model::works::{CommunityProfile, Language, Repository, TechnologyStack, WorksData};
 use reqwest::blocking::Client;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde::de::DeserializeOwned;
 
 use crate::{RepositoryAdditionalInformation, TOKEN, USER_NAME};
@@ -9,159 +11,85 @@ use crate::{RepositoryAdditionalInformation, TOKEN, USER_NAME};
 const APP_NAME: &str = "works_generator";
 const FETCH_SIZE: usize = 100;
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-pub struct Repository {
-    pub html_url: String,
-    pub name: String,
-    pub homepage: Option<String>,
-    pub language: RepositoryLanguage,
-    pub community_profile: Option<CommunityProfile>,
-    #[serde(default)]
-    pub technology_stacks: RepositoryTechnologyStacks,
-    #[serde(default)]
-    pub related_repositories: RepositoryRelatedRepositories,
+/// Repository as listed by the GitHub API
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct RepositoryResponse {
+    html_url: String,
+    name: String,
+    homepage: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-#[serde(untagged)]
-pub enum RepositoryLanguage {
-    None,
-    Single(String),
-    StringList(HashMap<String, usize>),
-    IndexList(Vec<(usize, usize)>),
+/// Repository with its languages, technologies and relations by name, before they are indexed
+struct NamedRepository {
+    response: RepositoryResponse,
+    community_profile: CommunityProfile,
+    languages: HashMap<String, usize>,
+    technology_stacks: Vec<String>,
+    related_repositories: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-#[serde(untagged)]
-pub enum RepositoryTechnologyStacks {
-    None,
-    StringList(Vec<String>),
-    IndexList(Vec<usize>),
-}
-
-impl Default for RepositoryTechnologyStacks {
-    fn default() -> Self {
-        RepositoryTechnologyStacks::None
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-#[serde(untagged)]
-pub enum RepositoryRelatedRepositories {
-    None,
-    StringList(Vec<String>),
-    IndexList(Vec<usize>),
-}
-
-impl Default for RepositoryRelatedRepositories {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-pub struct CommunityProfile {
-    health_percentage: usize,
-    description: Option<String>,
-    documentation: Option<String>,
-    files: CommunityProfileFiles,
-}
-
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-pub struct CommunityProfileFiles {
-    license: Option<HashMap<String, Option<String>>>,
-    readme: Option<HashMap<String, String>>,
-}
-
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Language {
-    pub name: String,
-    pub link: Option<String>,
-}
-
-#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
-pub struct TechnologyStack {
-    pub name: String,
-    pub link: Option<String>,
-    pub description: Option<String>,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct Works {
-    pub repositories: Vec<Repository>,
-    pub languages: Vec<Language>,
-    pub technologies: Vec<TechnologyStack>,
-}
-
-pub(crate) fn get_works(info: RepositoryAdditionalInformation) -> Works {
+pub(crate) fn get_works(info: RepositoryAdditionalInformation) -> WorksData {
     let client = Client::new();
     let mut repositories = Vec::new();
     for i in 1.. {
-        let mut result: Vec<Repository> = fetch_json(&client, &format!("/users/{}/repos?type=owner&per_page={}&page={}", USER_NAME.get().unwrap(), FETCH_SIZE, i));
+        let mut result: Vec<RepositoryResponse> = fetch_json(&client, &format!("/users/{}/repos?type=owner&per_page={}&page={}", USER_NAME.get().unwrap(), FETCH_SIZE, i));
         let result_len = result.len();
         repositories.append(&mut result);
         if result_len < FETCH_SIZE { break; }
     }
     let RepositoryAdditionalInformation { mut technology_stacks, mut repository_relations, technology_stack_info, language_info } = info;
-    let mut repositories = repositories.into_iter()
-        .filter_map(|mut repository| {
-            let community_profile = get_community_profile(&client, &USER_NAME.get().unwrap(), &repository.name);
-            if let None = community_profile.description { return None; }
-            repository.community_profile = Some(community_profile);
-            let languages = get_languages(&client, &USER_NAME.get().unwrap(), &repository.name);
-            repository.language = RepositoryLanguage::StringList(languages);
-            repository.technology_stacks = RepositoryTechnologyStacks::StringList(technology_stacks.remove(&repository.name).unwrap_or_default());
-            repository.related_repositories = RepositoryRelatedRepositories::StringList(repository_relations.remove(&repository.name).unwrap_or_default());
-            Some(repository)
+    let repositories = repositories.into_iter()
+        .filter_map(|repository| {
+            let community_profile = get_community_profile(&client, USER_NAME.get().unwrap(), &repository.name);
+            community_profile.description.as_ref()?;
+            Some(NamedRepository {
+                languages: get_languages(&client, USER_NAME.get().unwrap(), &repository.name),
+                technology_stacks: technology_stacks.remove(&repository.name).unwrap_or_default(),
+                related_repositories: repository_relations.remove(&repository.name).unwrap_or_default(),
+                response: repository,
+                community_profile,
+            })
         })
-        .collect();
+        .collect::<Vec<_>>();
     let (repo, lang, tech) = create_index_map(&repositories);
-    for r in &mut repositories {
-        let list = if let RepositoryLanguage::StringList(list) = std::mem::replace(&mut r.language, RepositoryLanguage::None) {
-            list.into_iter().map(|(k, v)| (*lang.get(&k).unwrap(), v)).collect()
-        } else { unreachable!() };
-        r.language = RepositoryLanguage::IndexList(list);
-        let list = if let RepositoryTechnologyStacks::StringList(list) = std::mem::replace(&mut r.technology_stacks, RepositoryTechnologyStacks::None) {
-            list.into_iter().map(|name| *tech.get(&name).unwrap()).collect()
-        } else { unreachable!() };
-        r.technology_stacks = RepositoryTechnologyStacks::IndexList(list);
-        let list = if let RepositoryRelatedRepositories::StringList(list) = std::mem::replace(&mut r.related_repositories, RepositoryRelatedRepositories::None) {
-            list.into_iter().map(|name| *repo.get(&name).unwrap()).collect()
-        } else { unreachable!() };
-        r.related_repositories = RepositoryRelatedRepositories::IndexList(list);
-    }
+    let repositories = repositories.into_iter()
+        .map(|r| Repository {
+            html_url: r.response.html_url,
+            name: r.response.name,
+            homepage: r.response.homepage,
+            language: r.languages.into_iter().map(|(k, v)| (*lang.get(&k).unwrap(), v)).collect(),
+            community_profile: r.community_profile,
+            technology_stacks: r.technology_stacks.into_iter().map(|name| *tech.get(&name).unwrap()).collect(),
+            related_repositories: r.related_repositories.into_iter().map(|name| *repo.get(&name).unwrap()).collect(),
+        })
+        .collect();
     let mut technology_stack_info = technology_stack_info.into_iter().map(|tech| (tech.name.clone(), tech)).collect::<HashMap<_, _>>();
     let mut language_info = language_info.into_iter().map(|lang| (lang.name.clone(), lang)).collect::<HashMap<_, _>>();
-    Works {
+    WorksData {
+        format_version: FORMAT_VERSION,
         repositories,
-        languages: lang.into_iter().map(|(name, _)| language_info.remove(&name).unwrap_or(Language { name, ..Default::default() })).collect(),
-        technologies: tech.into_iter().map(|(name, _)| technology_stack_info.remove(&name).unwrap_or(TechnologyStack { name, ..Default::default() })).collect(),
+        languages: lang.into_keys().map(|name| language_info.remove(&name).unwrap_or(Language { name, ..Default::default() })).collect(),
+        technologies: tech.into_keys().map(|name| technology_stack_info.remove(&name).unwrap_or(TechnologyStack { name, ..Default::default() })).collect(),
     }
 }
 
-fn create_index_map(repos: &Vec<Repository>) -> (BTreeMap<String, usize>, BTreeMap<String, usize>, BTreeMap<String, usize>) {
+fn create_index_map(repos: &Vec<NamedRepository>) -> (BTreeMap<String, usize>, BTreeMap<String, usize>, BTreeMap<String, usize>) {
     let mut repository_name = BTreeMap::new();
     let mut language = BTreeSet::new();
     let mut technology_stack = BTreeSet::new();
     for repo in repos {
-        repository_name.insert(repo.name.clone(), repository_name.len());
-        match &repo.language {
-            RepositoryLanguage::StringList(list) => for lang in list.keys() {
-                if !language.contains(lang) { language.insert(lang.clone()); }
-            }
-            _ => unreachable!(),
+        repository_name.insert(repo.response.name.clone(), repository_name.len());
+        for lang in repo.languages.keys() {
+            if !language.contains(lang) { language.insert(lang.clone()); }
         }
-        match &repo.technology_stacks {
-            RepositoryTechnologyStacks::StringList(list) => for tech in list {
-                if !technology_stack.contains(tech) { technology_stack.insert(tech.clone()); }
-            }
-            _ => unreachable!()
+        for tech in &repo.technology_stacks {
+            if !technology_stack.contains(tech) { technology_stack.insert(tech.clone()); }
         }
     }
     (repository_name, language.into_iter().enumerate().map(|(a, b)| (b, a)).collect(), technology_stack.into_iter().enumerate().map(|(a, b)| (b, a)).collect())
 }
 
-fn fetch_json<'a, T: DeserializeOwned>(client: &Client, url: &str) -> T {
+fn fetch_json<T: DeserializeOwned>(client: &Client, url: &str) -> T {
     let response = client.get(format!("https://api.github.com{}", url))
         .header("User-Agent", APP_NAME)
         .header("accept", "application/vnd.github.v3+json")
@@ -171,7 +99,7 @@ fn fetch_json<'a, T: DeserializeOwned>(client: &Client, url: &str) -> T {
     let mut body: &[u8] = &body;
     match serde_json::from_reader(&mut body) {
         Ok(result) => result,
-        Err(e) => panic!("Error '{}' by '{}'", e, String::from_utf8_lossy(&body)),
+        Err(e) => panic!("Error '{}' by '{}'", e, String::from_utf8_lossy(body)),
     }
 }
 